@@ -0,0 +1,158 @@
+//! user configuration, read from a file of `key = value` lines
+
+use std::{io, path::Path, str::FromStr};
+
+use crate::{custom::Spec, targets::Goals, theme::Theme};
+
+pub struct Config {
+    /// the amount `+` and `-` change an entry's quantity by
+    pub step: f64,
+
+    /// whether to show raw macro totals. these can be hidden when using
+    /// exchanges or points instead
+    pub show_macros: bool,
+
+    /// whether to show how the day's totals differ from the day before and
+    /// from the average over the week before
+    pub deltas: bool,
+
+    /// whether to show diabetic carb exchanges (15 g of carbs each)
+    pub exchanges: bool,
+
+    /// a formula for computing points from an entry's calories, protein,
+    /// carbs, fat, and optional nutrients, like
+    /// `(calories + 4 * fat - 10 * protein) / 33`
+    pub points: Option<String>,
+
+    /// a formula for scoring the nutrient density of a food from the calories,
+    /// protein, carbs, fat, and optional nutrients in one unit of it, like
+    /// `100 * (protein + 3 * fiber) / calories`. the score is shown next to
+    /// recent foods and in the Numpad food list
+    pub density: Option<String>,
+
+    /// the daily goals, unless overridden for a particular day. the calorie
+    /// goal is set with the `budget` key and the others with `protein_goal`,
+    /// `carbs_goal`, and `fat_goal`
+    pub goals: Goals,
+
+    /// the number of idle minutes after which to blank the screen
+    pub lock_after: Option<u64>,
+
+    /// a passphrase required to resume after locking, instead of any key
+    pub lock_passphrase: Option<String>,
+
+    /// the serial device of a kitchen scale to read quantities from, when built
+    /// with the `scale` feature. the port itself, like its baud rate, is set up
+    /// outside of macroni with `stty`
+    pub scale: Option<String>,
+
+    /// whether the Day summary report explains what stood out about the day
+    pub assess: bool,
+
+    /// the custom reports defined with `report` keys, shown after the built-in
+    /// ones in the Reports menu
+    pub reports: Vec<Spec>,
+
+    /// whether to use vim-like keys: `j` and `k` to move through lists, `h` and
+    /// `l` to change days, and `:` for a command line
+    pub vim: bool,
+
+    /// whether to draw boxes with ASCII characters instead of unicode ones, set
+    /// with `borders = ascii` or `borders = unicode`. when unset, this follows
+    /// the locale
+    pub ascii_borders: Option<bool>,
+
+    /// the colors of the interface, set with `color_` keys like `color_accent`
+    pub theme: Theme,
+
+    /// why the lines of the config that could not be applied were ignored
+    pub errors: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            step: 1.0,
+            show_macros: true,
+            deltas: true,
+            exchanges: false,
+            points: None,
+            density: None,
+            goals: Goals::default(),
+            lock_after: None,
+            lock_passphrase: None,
+            scale: None,
+            assess: true,
+            reports: Vec::new(),
+            vim: false,
+            ascii_borders: None,
+            theme: Theme::default(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// parse `value` into `field`, leaving it unchanged if `value` is invalid
+fn set<T: FromStr>(field: &mut T, value: &str) {
+    if let Ok(v) = value.parse() {
+        *field = v;
+    }
+}
+
+impl Config {
+    /// load the configuration from `path`. a missing file gives the default
+    /// configuration, and unknown keys or invalid values are ignored. invalid
+    /// colors are also listed in [Config::errors]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let s = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut ret = Self::default();
+        for line in s.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "step" => set(&mut ret.step, value),
+                "show_macros" => set(&mut ret.show_macros, value),
+                "deltas" => set(&mut ret.deltas, value),
+                "exchanges" => set(&mut ret.exchanges, value),
+                "points" => ret.points = Some(value.to_owned()),
+                "density" => ret.density = Some(value.to_owned()),
+                "budget" => ret.goals.calories = value.parse().ok(),
+                "protein_goal" => ret.goals.protein = value.parse().ok(),
+                "carbs_goal" => ret.goals.carbs = value.parse().ok(),
+                "fat_goal" => ret.goals.fat = value.parse().ok(),
+                "lock_after" => ret.lock_after = value.parse().ok(),
+                "lock_passphrase" => {
+                    ret.lock_passphrase = Some(value.to_owned())
+                }
+                "scale" => ret.scale = Some(value.to_owned()),
+                "assess" => set(&mut ret.assess, value),
+                "report" => ret.reports.extend(value.parse().ok()),
+                "vim" => set(&mut ret.vim, value),
+                "borders" => {
+                    ret.ascii_borders = match value {
+                        "ascii" => Some(true),
+                        "unicode" => Some(false),
+                        _ => None,
+                    }
+                }
+                key if key.starts_with("color_") => {
+                    if let Err(e) = ret.theme.set(&key["color_".len()..], value)
+                    {
+                        ret.errors.push(format!("{key}: {e}"));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(ret)
+    }
+}
@@ -0,0 +1,193 @@
+//! reports defined by the user in the config, like
+//!
+//! ```text
+//! report = Weekly protein; metrics = protein; range = 56; group = week
+//! ```
+//!
+//! where everything but the name is optional
+
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::{log::Log, NUTRIENTS};
+
+/// the days a custom report covers, all ending on the current day
+#[derive(Clone, Copy)]
+pub enum Range {
+    /// the last `n` days
+    Days(u64),
+    /// the current month so far
+    Month,
+    /// the current year so far
+    Year,
+    /// every day since the first one logged
+    All,
+}
+
+/// how a custom report groups the logged entries into rows
+#[derive(Clone, Copy)]
+pub enum Group {
+    /// a row of totals for each logged day
+    Day,
+    /// a row for each week starting on Monday, averaged over its logged days
+    Week,
+    /// a row for each calendar month, averaged over its logged days
+    Month,
+    /// a row for each day of the week, averaged over those logged
+    Weekday,
+    /// a row for each part of the day, averaged over all logged days
+    Meal,
+    /// a row of totals for each food, largest first
+    Food,
+}
+
+/// a named report over a set of metrics, which are `calories`, `protein`,
+/// `carbs`, `fat`, or the optional nutrients in lower case
+#[derive(Clone)]
+pub struct Spec {
+    pub name: String,
+    pub metrics: Vec<String>,
+    pub range: Range,
+    pub group: Group,
+}
+
+impl Range {
+    /// the first and last days covered when the current day is `to`. a number
+    /// of days reaching past the earliest date starts on the first day logged
+    pub fn dates(&self, log: &Log, to: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let first = || log.span().map_or(to, |(first, _)| first);
+        let from = match self {
+            Range::Days(n) => to
+                .checked_sub_days(Days::new(n.saturating_sub(1)))
+                .unwrap_or_else(first),
+            Range::Month => to.with_day(1).unwrap(),
+            Range::Year => to.with_ordinal(1).unwrap(),
+            Range::All => first(),
+        };
+        (from, to)
+    }
+}
+
+impl FromStr for Spec {
+    type Err = String;
+
+    /// parse the name followed by optional `key = value` settings, separated by
+    /// semicolons
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        if name.is_empty() {
+            return Err("missing report name".to_owned());
+        }
+        let mut ret = Self {
+            name: name.to_owned(),
+            metrics: ["calories", "protein", "carbs", "fat"]
+                .map(str::to_owned)
+                .to_vec(),
+            range: Range::Days(30),
+            group: Group::Day,
+        };
+        for part in parts.filter(|part| !part.trim().is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                return Err(format!("expected `key = value`, got `{part}`"));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "metrics" => {
+                    ret.metrics = value
+                        .split(',')
+                        .map(|m| m.trim().to_lowercase())
+                        .collect();
+                    let known = |m: &String| {
+                        ["calories", "protein", "carbs", "fat"]
+                            .contains(&m.as_str())
+                            || NUTRIENTS
+                                .iter()
+                                .any(|n| n.eq_ignore_ascii_case(m))
+                    };
+                    if let Some(m) = ret.metrics.iter().find(|m| !known(m)) {
+                        return Err(format!("unknown metric `{m}`"));
+                    }
+                }
+                "range" => {
+                    ret.range = match value {
+                        "month" => Range::Month,
+                        "year" => Range::Year,
+                        "all" => Range::All,
+                        n => Range::Days(
+                            n.parse()
+                                .map_err(|_| format!("bad range `{n}`"))?,
+                        ),
+                    }
+                }
+                "group" => {
+                    ret.group = match value {
+                        "day" => Group::Day,
+                        "week" => Group::Week,
+                        "month" => Group::Month,
+                        "weekday" => Group::Weekday,
+                        "meal" => Group::Meal,
+                        "food" => Group::Food,
+                        g => return Err(format!("unknown group `{g}`")),
+                    }
+                }
+                k => return Err(format!("unknown setting `{k}`")),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::Entry;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, crate::log::DATE_FMT).unwrap()
+    }
+
+    #[test]
+    fn bad_settings() {
+        let err = |s: &str| s.parse::<Spec>().err().unwrap();
+        assert_eq!(err("; range = 7"), "missing report name");
+        assert_eq!(
+            err("R; metrics = fat, fiber, sugarz"),
+            "unknown metric `sugarz`"
+        );
+        assert_eq!(err("R; range = soon"), "bad range `soon`");
+        assert_eq!(err("R; range = -3"), "bad range `-3`");
+        assert_eq!(err("R; group = hour"), "unknown group `hour`");
+        assert_eq!(err("R; colour = red"), "unknown setting `colour`");
+        assert_eq!(err("R; week"), "expected `key = value`, got ` week`");
+
+        let spec: Spec = "R; metrics = Protein, FIBER; group = meal;"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.metrics, ["protein", "fiber"]);
+        assert!(matches!(spec.range, Range::Days(30)));
+        assert!(matches!(spec.group, Group::Meal));
+    }
+
+    #[test]
+    fn days_start_on_first_logged_day() {
+        // the directory does not exist, so this loads an empty log
+        let dir = std::env::temp_dir()
+            .join(format!("macroni-custom-test-{}", std::process::id()));
+        let mut log = Log::load(dir.join("entries")).unwrap();
+        let to = date("2024-03-10");
+        assert_eq!(Range::Days(u64::MAX).dates(&log, to), (to, to));
+
+        let food = "Bun\t140\t2\t25\t4\tbun".parse().unwrap();
+        log.add(date("2024-01-15"), Entry::new(food, 1.0));
+        assert_eq!(
+            Range::Days(u64::MAX).dates(&log, to),
+            (date("2024-01-15"), to)
+        );
+        assert_eq!(Range::Days(7).dates(&log, to), (date("2024-03-04"), to));
+        assert_eq!(Range::Days(0).dates(&log, to), (to, to));
+        assert_eq!(Range::Month.dates(&log, to), (date("2024-03-01"), to));
+        assert_eq!(Range::All.dates(&log, to), (date("2024-01-15"), to));
+    }
+}
@@ -0,0 +1,172 @@
+//! comparison of two snapshots of the foods and log files, for checking what a
+//! sync or import would change before accepting it
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::Serialize;
+
+use crate::{log::Log, Food};
+
+/// a line that differs between the two snapshots
+#[derive(Serialize)]
+pub struct Change {
+    pub old: String,
+    pub new: String,
+}
+
+/// the lines added, removed, and changed between two snapshots of a file
+#[derive(Default, Serialize)]
+pub struct Changes {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<Change>,
+}
+
+impl Changes {
+    /// pair up the lines in `old` and `new` by their keys, treating a key in
+    /// both with different lines as a change. lines that appear in both are
+    /// left out, and a key may be repeated, as for the same food logged twice
+    /// at once
+    fn new<K: PartialEq>(old: Vec<(K, String)>, new: Vec<(K, String)>) -> Self {
+        let mut new: Vec<Option<(K, String)>> =
+            new.into_iter().map(Some).collect();
+        let mut unmatched = Vec::new();
+        for (key, line) in old {
+            match new
+                .iter_mut()
+                .find(|n| n.as_ref().is_some_and(|n| n.1 == line))
+            {
+                Some(n) => *n = None,
+                None => unmatched.push((key, line)),
+            }
+        }
+        let mut ret = Self::default();
+        for (key, line) in unmatched {
+            match new
+                .iter_mut()
+                .find(|n| n.as_ref().is_some_and(|n| n.0 == key))
+            {
+                Some(n) => ret.changed.push(Change {
+                    old: line,
+                    new: n.take().unwrap().1,
+                }),
+                None => ret.removed.push(line),
+            }
+        }
+        ret.added = new.into_iter().flatten().map(|(_, line)| line).collect();
+        ret
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// a summary line followed by the changes themselves, marked with `+`, `-`,
+    /// and `~`
+    fn lines(&self, name: &str) -> Vec<String> {
+        let mut ret = vec![format!(
+            "{name}: {} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )];
+        ret.extend(self.added.iter().map(|line| format!("+ {line}")));
+        ret.extend(self.removed.iter().map(|line| format!("- {line}")));
+        for Change { old, new } in &self.changed {
+            ret.push(format!("~ {old}"));
+            ret.push(format!("  {new}"));
+        }
+        ret
+    }
+}
+
+/// the differences between two snapshots. foods are matched by name and entries
+/// by their date, time, and food
+#[derive(Serialize)]
+pub struct Diff {
+    pub foods: Changes,
+    pub entries: Changes,
+}
+
+/// the key entries are matched by
+type EntryKey = (NaiveDate, Option<NaiveTime>, String);
+
+fn entry_lines(log: &Log) -> Vec<(EntryKey, String)> {
+    log.range(NaiveDate::MIN, NaiveDate::MAX)
+        .flat_map(|(date, entries)| {
+            entries.iter().map(move |e| {
+                ((date, e.time, e.food.name.clone()), e.to_line(date))
+            })
+        })
+        .collect()
+}
+
+fn food_lines(foods: &[Food]) -> Vec<(String, String)> {
+    foods
+        .iter()
+        .map(|f| (f.name.clone(), f.to_string()))
+        .collect()
+}
+
+impl Diff {
+    pub fn new(
+        old_foods: &[Food],
+        old_log: &Log,
+        new_foods: &[Food],
+        new_log: &Log,
+    ) -> Self {
+        Self {
+            foods: Changes::new(food_lines(old_foods), food_lines(new_foods)),
+            entries: Changes::new(entry_lines(old_log), entry_lines(new_log)),
+        }
+    }
+
+    /// the differences as readable lines, or a single line if there are none
+    pub fn lines(&self) -> Vec<String> {
+        if self.foods.is_empty() && self.entries.is_empty() {
+            return vec!["no differences".to_owned()];
+        }
+        let mut ret = self.foods.lines("foods");
+        ret.push(String::new());
+        ret.extend(self.entries.lines("entries"));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[(&str, &str)]) -> Vec<(String, String)> {
+        lines
+            .iter()
+            .map(|(key, line)| (key.to_string(), line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn repeated_keys() {
+        // the second `a 1` pairs with the changed line under the same key
+        let changes = Changes::new(
+            lines(&[("a", "a 1"), ("a", "a 1"), ("b", "b 1")]),
+            lines(&[("a", "a 2"), ("a", "a 1"), ("c", "c 1")]),
+        );
+        assert_eq!(changes.added, ["c 1"]);
+        assert_eq!(changes.removed, ["b 1"]);
+        assert_eq!(changes.changed.len(), 1);
+        assert_eq!(changes.changed[0].old, "a 1");
+        assert_eq!(changes.changed[0].new, "a 2");
+
+        // identical repeats are counted, not collapsed
+        let changes = Changes::new(
+            lines(&[("a", "a 1"), ("a", "a 1")]),
+            lines(&[("a", "a 1")]),
+        );
+        assert_eq!(changes.removed, ["a 1"]);
+        assert!(changes.added.is_empty() && changes.changed.is_empty());
+
+        let same = lines(&[("a", "a 1"), ("a", "a 1")]);
+        assert!(Changes::new(same.clone(), same).is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+//! dated events like "started creatine" that give context to changes in intake
+
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+
+use crate::log::DATE_FMT;
+
+/// the recorded events, stored as a tsv file of dates and descriptions
+pub struct Events {
+    path: PathBuf,
+    days: BTreeMap<NaiveDate, Vec<String>>,
+}
+
+impl Events {
+    /// load the events from `path`. a missing file is treated as empty
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let s = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut days: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+        for line in s.lines().filter(|line| !line.starts_with('#')) {
+            let Some((date, text)) = line.split_once('\t') else {
+                continue;
+            };
+            let Ok(date) = NaiveDate::parse_from_str(date, DATE_FMT) else {
+                continue;
+            };
+            days.entry(date).or_default().push(text.to_owned());
+        }
+        Ok(Self { path, days })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut s = String::from("# Date\tEvent\n");
+        for (date, events) in &self.days {
+            for event in events {
+                s.push_str(&format!("{}\t{event}\n", date.format(DATE_FMT)));
+            }
+        }
+        std::fs::write(&self.path, s)
+    }
+
+    /// the events recorded on `date`
+    pub fn day(&self, date: NaiveDate) -> &[String] {
+        self.days.get(&date).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// record `event` on `date`
+    pub fn add(&mut self, date: NaiveDate, event: &str) {
+        let event = event.replace(['\t', '\n'], " ");
+        self.days.entry(date).or_default().push(event);
+    }
+}
@@ -0,0 +1,123 @@
+//! export of the log to Arrow IPC files for analysis in tools like pandas and
+//! polars
+
+use std::{error::Error, fs::File, path::Path, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Date32Array, Float64Array, RecordBatch,
+    StringArray, Time32SecondArray, UInt32Array,
+};
+use arrow_ipc::writer::FileWriter;
+use chrono::{DateTime, NaiveDate, NaiveTime, Timelike};
+
+use crate::{log::TIME_FMT, model, NUTRIENTS};
+
+/// the number of days since the Unix epoch, as stored in Arrow date columns
+fn epoch_days(date: NaiveDate) -> i32 {
+    (date - DateTime::UNIX_EPOCH.date_naive()).num_days() as i32
+}
+
+fn write(
+    path: &Path,
+    columns: Vec<(&str, ArrayRef)>,
+) -> Result<(), Box<dyn Error>> {
+    let batch = RecordBatch::try_from_iter(columns)?;
+    let mut writer = FileWriter::try_new(File::create(path)?, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// write one row per entry in `days` to `entries.arrow` and one row of totals
+/// per day with entries to `days.arrow` in `dir`. the macros and nutrients of
+/// each entry are the amounts actually eaten, and nutrients unknown for a food
+/// are null
+pub fn arrow(days: &[model::Day], dir: &Path) -> Result<(), Box<dyn Error>> {
+    let mut dates = Vec::new();
+    let mut times = Vec::new();
+    let mut names = Vec::new();
+    let mut units = Vec::new();
+    let mut quantities = Vec::new();
+    let mut consumed = Vec::new();
+    let mut estimated = Vec::new();
+    let mut macros = [const { Vec::new() }; 4];
+    let mut nutrients = [const { Vec::new() }; NUTRIENTS.len()];
+    let mut notes = Vec::new();
+
+    let mut day_dates = Vec::new();
+    let mut day_counts = Vec::new();
+    let mut day_macros = [const { Vec::new() }; 4];
+
+    let nutrient_names: Vec<String> =
+        NUTRIENTS.iter().map(|n| n.to_lowercase()).collect();
+    for day in days.iter().filter(|day| !day.entries.is_empty()) {
+        let date = epoch_days(day.naive_date());
+        for entry in &day.entries {
+            dates.push(date);
+            times.push(entry.time.as_deref().map(|t| {
+                let t = NaiveTime::parse_from_str(t, TIME_FMT).unwrap();
+                t.num_seconds_from_midnight() as i32
+            }));
+            names.push(entry.food.name.clone());
+            units.push(entry.food.unit.clone());
+            quantities.push(entry.quantity);
+            consumed.push(entry.consumed);
+            estimated.push(entry.estimated);
+            let m = entry.totals();
+            for (col, v) in macros
+                .iter_mut()
+                .zip([m.calories, m.protein, m.carbs, m.fat])
+            {
+                col.push(v);
+            }
+            for (col, name) in nutrients.iter_mut().zip(&nutrient_names) {
+                col.push(entry.nutrient(name));
+            }
+            notes.push(entry.note.clone());
+        }
+        let m = day.totals;
+        day_dates.push(date);
+        day_counts.push(day.entries.len() as u32);
+        for (col, v) in day_macros
+            .iter_mut()
+            .zip([m.calories, m.protein, m.carbs, m.fat])
+        {
+            col.push(v);
+        }
+    }
+
+    let [calories, protein, carbs, fat] =
+        macros.map(|col| Arc::new(Float64Array::from(col)) as ArrayRef);
+    let mut columns: Vec<(&str, ArrayRef)> = vec![
+        ("date", Arc::new(Date32Array::from(dates))),
+        ("time", Arc::new(Time32SecondArray::from(times))),
+        ("food", Arc::new(StringArray::from(names))),
+        ("unit", Arc::new(StringArray::from(units))),
+        ("quantity", Arc::new(Float64Array::from(quantities))),
+        ("consumed", Arc::new(Float64Array::from(consumed))),
+        ("estimated", Arc::new(BooleanArray::from(estimated))),
+        ("calories", calories),
+        ("protein", protein),
+        ("carbs", carbs),
+        ("fat", fat),
+    ];
+    for (name, col) in nutrient_names.iter().zip(nutrients) {
+        columns.push((name, Arc::new(Float64Array::from(col))));
+    }
+    columns.push(("note", Arc::new(StringArray::from(notes))));
+    write(&dir.join("entries.arrow"), columns)?;
+
+    let [calories, protein, carbs, fat] =
+        day_macros.map(|col| Arc::new(Float64Array::from(col)) as ArrayRef);
+    write(
+        &dir.join("days.arrow"),
+        vec![
+            ("date", Arc::new(Date32Array::from(day_dates))),
+            ("entries", Arc::new(UInt32Array::from(day_counts))),
+            ("calories", calories),
+            ("protein", protein),
+            ("carbs", carbs),
+            ("fat", fat),
+        ],
+    )
+}
@@ -0,0 +1,211 @@
+//! evaluation of the simple arithmetic accepted in numeric fields, like `1/2`,
+//! `1 1/2`, and `3*85`, and of user-configured formulas over named values
+
+use std::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    Num(f64),
+    Ident(&'a str),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>, Box<dyn Error>> {
+    let mut ret = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let tok = match c {
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' | '×' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = i + c.len_utf8();
+                while let Some(&(j, d)) = chars.peek() {
+                    if !(d.is_ascii_digit() || d == '.') {
+                        break;
+                    }
+                    end = j + d.len_utf8();
+                    chars.next();
+                }
+                Token::Num(s[i..end].parse()?)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = i + c.len_utf8();
+                while let Some(&(j, d)) = chars.peek() {
+                    if !(d.is_alphabetic() || d == '_') {
+                        break;
+                    }
+                    end = j + d.len_utf8();
+                    chars.next();
+                }
+                // a lone x between numbers, as in 3x85, means multiplication
+                match &s[i..end] {
+                    "x" => Token::Star,
+                    ident => Token::Ident(ident),
+                }
+            }
+            c if c.is_whitespace() => continue,
+            c => Err(format!("unexpected `{c}`"))?,
+        };
+        ret.push(tok);
+    }
+    Ok(ret)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    vars: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let ret = self.peek();
+        self.pos += 1;
+        ret
+    }
+
+    /// the value of the next tokens if they are a fraction like `1/2`, as in
+    /// the second half of the mixed number `1 1/2`
+    fn fraction(&self) -> Option<f64> {
+        match self.tokens.get(self.pos..self.pos + 3)? {
+            [Token::Num(a), Token::Slash, Token::Num(b)] => Some(a / b),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64, Box<dyn Error>> {
+        let mut ret = self.term()?;
+        loop {
+            match self.peek() {
+                Some(Token::Plus) => {
+                    self.next();
+                    ret += self.term()?;
+                }
+                Some(Token::Minus) => {
+                    self.next();
+                    ret -= self.term()?;
+                }
+                _ => return Ok(ret),
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, Box<dyn Error>> {
+        let mut ret = self.factor()?;
+        loop {
+            match self.peek() {
+                Some(Token::Star) => {
+                    self.next();
+                    ret *= self.factor()?;
+                }
+                Some(Token::Slash) => {
+                    self.next();
+                    ret /= self.factor()?;
+                }
+                _ => return Ok(ret),
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<f64, Box<dyn Error>> {
+        match self.next() {
+            // a mixed number is one literal, so that `2 * 1 1/2` is 3
+            Some(Token::Num(n)) => match self.fraction() {
+                Some(fraction) => {
+                    self.pos += 3;
+                    Ok(n + fraction)
+                }
+                None => Ok(n),
+            },
+            Some(Token::Ident(name)) => match (self.vars)(name) {
+                Some(v) => Ok(v),
+                None => Err(format!("unknown name `{name}`"))?,
+            },
+            Some(Token::Minus) => Ok(-self.factor()?),
+            Some(Token::LParen) => {
+                let ret = self.expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(ret),
+                    _ => Err("unclosed `(`")?,
+                }
+            }
+            Some(_) => Err("expected a number")?,
+            None => Err("unexpected end of input")?,
+        }
+    }
+}
+
+/// evaluate `s` as a number, a fraction, a mixed number, or a simple arithmetic
+/// expression using `+ - * /` and parentheses
+pub fn eval(s: &str) -> Result<f64, Box<dyn Error>> {
+    eval_with(s, &|_| None)
+}
+
+/// like [eval], but also allow names in the expression, looking up their values
+/// with `vars`
+pub fn eval_with(
+    s: &str,
+    vars: &dyn Fn(&str) -> Option<f64>,
+) -> Result<f64, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        vars,
+    };
+    let ret = parser.expr()?;
+    if parser.pos < parser.tokens.len() {
+        Err("unexpected input after expression")?;
+    }
+    if !ret.is_finite() {
+        Err("result is not a finite number")?;
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractions() {
+        assert_eq!(eval("1/2").unwrap(), 0.5);
+        assert_eq!(eval("3/4").unwrap(), 0.75);
+    }
+
+    #[test]
+    fn mixed_numbers() {
+        assert_eq!(eval("1 1/2").unwrap(), 1.5);
+        assert_eq!(eval("-1 1/2").unwrap(), -1.5);
+        assert_eq!(eval("1 - 1 1/2").unwrap(), -0.5);
+        assert_eq!(eval("2 * 1 1/2").unwrap(), 3.0);
+        assert_eq!(eval("1 1/2 * 2").unwrap(), 3.0);
+    }
+
+    #[test]
+    fn products() {
+        assert_eq!(eval("3*85").unwrap(), 255.0);
+        assert_eq!(eval("3x85").unwrap(), 255.0);
+        assert_eq!(eval("3 x 85").unwrap(), 255.0);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(eval("").is_err());
+        assert!(eval("1 2").is_err());
+        assert!(eval("1/0").is_err());
+        assert!(eval("(1").is_err());
+    }
+}
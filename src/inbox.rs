@@ -0,0 +1,69 @@
+//! quick placeholder notes to be turned into proper entries later
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDateTime;
+
+use crate::log::{DATE_FMT, TIME_FMT};
+
+/// a raw note about something eaten and when it was written down
+#[derive(Clone)]
+pub struct Capture {
+    pub stamp: NaiveDateTime,
+    pub text: String,
+}
+
+/// the list of unresolved captures, stored as a tsv file of timestamps and
+/// text
+pub struct Inbox {
+    path: PathBuf,
+    pub items: Vec<Capture>,
+}
+
+/// the format of the timestamp column in the inbox file
+fn stamp_fmt() -> String {
+    format!("{DATE_FMT} {TIME_FMT}")
+}
+
+impl Capture {
+    /// the capture's timestamp formatted as in the inbox file
+    pub fn format_stamp(&self) -> String {
+        self.stamp.format(&stamp_fmt()).to_string()
+    }
+}
+
+impl Inbox {
+    /// load the inbox from `path`. a missing file is treated as empty
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let s = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let fmt = stamp_fmt();
+        let items = s
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let (stamp, text) = line.split_once('\t')?;
+                Some(Capture {
+                    stamp: NaiveDateTime::parse_from_str(stamp, &fmt).ok()?,
+                    text: text.to_owned(),
+                })
+            })
+            .collect();
+        Ok(Self { path, items })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut s = String::from("# Time\tText\n");
+        for item in &self.items {
+            s.push_str(&format!("{}\t{}\n", item.format_stamp(), item.text));
+        }
+        std::fs::write(&self.path, s)
+    }
+}
@@ -0,0 +1,169 @@
+//! a line of editable text with its own cursor, for the fields of forms
+
+use std::ops::Deref;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+/// the text typed into a field and the position of the cursor in it, counted
+/// in characters
+#[derive(Clone, Default)]
+pub struct Input {
+    text: String,
+    cursor: usize,
+    /// earlier values of the field, oldest first, for recalling with Up and
+    /// Down
+    history: Vec<String>,
+    /// the index in [Input::history] being shown while recalling, and the
+    /// text that was typed before recalling began
+    recall: Option<(usize, String)>,
+}
+
+impl Deref for Input {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.text
+    }
+}
+
+impl Input {
+    /// replace the text with `text`, leaving the cursor at its end
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.text.chars().count();
+        self.recall = None;
+    }
+
+    /// add `text` to the end of the history, moving it there if it is already
+    /// in it. empty text is left out
+    pub fn push_history(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.history.retain(|old| old != text);
+        self.history.push(text.to_owned());
+    }
+
+    /// add the current text to the history
+    pub fn remember(&mut self) {
+        let text = self.text.clone();
+        self.push_history(&text);
+    }
+
+    /// whether an earlier value is being shown with Up and Down
+    pub fn is_recalling(&self) -> bool {
+        self.recall.is_some()
+    }
+
+    /// replace the text with the value in the history before the one shown if
+    /// `back`, or after it, returning to what was typed past the last one.
+    /// returns whether there was a value to move to
+    fn recall(&mut self, back: bool) -> bool {
+        let len = self.history.len();
+        let (i, typed) = self
+            .recall
+            .take()
+            .unwrap_or_else(|| (len, self.text.clone()));
+        let next = if back {
+            i.checked_sub(1)
+        } else {
+            Some(i + 1).filter(|&next| next <= len)
+        };
+        let Some(next) = next else {
+            if i < len {
+                self.recall = Some((i, typed));
+            }
+            return false;
+        };
+        match self.history.get(next) {
+            Some(text) => {
+                self.set(text.clone());
+                self.recall = Some((next, typed));
+            }
+            None => self.set(typed),
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    /// insert `s` at the cursor, leaving the cursor after it
+    pub fn insert_str(&mut self, s: &str) {
+        let offset = self.offset(self.cursor);
+        self.text.insert_str(offset, s);
+        self.cursor += s.chars().count();
+    }
+
+    /// the number of terminal columns taken up by the text before the cursor
+    pub fn cursor_width(&self) -> usize {
+        self.text[..self.offset(self.cursor)].width()
+    }
+
+    /// the byte offset of the character at `i`, or the length of the text if
+    /// it is past the end
+    fn offset(&self, i: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(i)
+            .map_or(self.text.len(), |(offset, _)| offset)
+    }
+
+    /// delete the characters from `from` up to the cursor and move the cursor
+    /// back to `from`
+    fn delete_back_to(&mut self, from: usize) {
+        let range = self.offset(from)..self.offset(self.cursor);
+        self.text.replace_range(range, "");
+        self.cursor = from;
+    }
+
+    /// apply an editing key to the text, returning whether it was one.
+    /// characters are inserted at the cursor, Backspace and Delete remove the
+    /// character before and after it, Ctrl-W removes the word before it,
+    /// Left, Right, Home, and End move it, and Up and Down recall the earlier
+    /// values of the field
+    pub fn handle(&mut self, event: KeyEvent) -> bool {
+        match event.code {
+            KeyCode::Up => return self.recall(true),
+            KeyCode::Down => return self.recall(false),
+            _ => self.recall = None,
+        }
+        let len = self.text.chars().count();
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+        match event.code {
+            KeyCode::Char('w') if ctrl => {
+                let chars: Vec<char> = self.text.chars().collect();
+                let mut from = self.cursor;
+                while from > 0 && chars[from - 1].is_whitespace() {
+                    from -= 1;
+                }
+                while from > 0 && !chars[from - 1].is_whitespace() {
+                    from -= 1;
+                }
+                self.delete_back_to(from);
+            }
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => {
+                let offset = self.offset(self.cursor);
+                self.text.insert(offset, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.delete_back_to(self.cursor - 1)
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.cursor += 1;
+                self.delete_back_to(self.cursor - 1);
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            _ => return false,
+        }
+        true
+    }
+}
@@ -0,0 +1,406 @@
+//! the keys of each screen, from which both the event loop and the help are
+//! generated so that they can't disagree
+
+use crossterm::event::{
+    KeyCode::{self, *},
+    KeyEvent, KeyModifiers,
+};
+
+/// what a key does. the first group is only bound on the main screen, the
+/// rest are shared by the other screens
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Help,
+    Quit,
+    AddFood,
+    Search,
+    QuickAdd,
+    Numpad,
+    SplitDish,
+    CopyDay,
+    Calendar,
+    Compare,
+    /// move to the previous or next day, by the arrow pressed
+    Day,
+    Today,
+    Reports,
+    Time,
+    /// change the quantity of the selected entry, by the key pressed
+    Quantity,
+    Weigh,
+    Repeat,
+    Undo,
+    Estimate,
+    Partial,
+    Note,
+    DayNote,
+    DayGoals,
+    Event,
+    Inbox,
+    Review,
+    History,
+    CompareFoods,
+    /// log one of the recent foods, by the digit pressed
+    Recent,
+    /// move the selection in the focused pane up or down, by the arrow pressed
+    Select,
+    /// move the focus to the next pane
+    Pane,
+    /// log the highlighted recent food, when the Recent pane has the focus
+    LogRecent,
+    /// open the command line
+    Command,
+
+    /// return to the screen before, dropping anything typed
+    Back,
+    /// act on the input or the highlighted item, by what the screen is for
+    Accept,
+    /// move to the next field
+    Next,
+    /// move to the previous field
+    Prev,
+    /// switch to the next search mode
+    SearchMode,
+    /// keep the entry on the form to submit with the next
+    Queue,
+    /// log the entries on the form
+    Submit,
+    /// read the quantity from the scale
+    Scale,
+    Delete,
+    Toggle,
+    /// move to the previous or next month, by the key pressed
+    Month,
+    /// shorten or lengthen the period of a report, by the arrow pressed
+    Period,
+    /// move to the previous or next year, by the arrow pressed
+    Year,
+}
+
+/// one or more keys on a screen doing the same thing
+pub struct Binding {
+    pub codes: &'static [KeyCode],
+    /// whether the keys only count with Ctrl held down
+    pub ctrl: bool,
+    /// the keys as shown in the help
+    pub keys: &'static str,
+    /// what the keys do, as shown in the help
+    pub desc: &'static str,
+    pub action: Action,
+}
+
+const fn bind(
+    codes: &'static [KeyCode],
+    keys: &'static str,
+    desc: &'static str,
+    action: Action,
+) -> Binding {
+    Binding {
+        codes,
+        ctrl: false,
+        keys,
+        desc,
+        action,
+    }
+}
+
+/// [bind] `codes` with Ctrl held down
+const fn ctrl(
+    codes: &'static [KeyCode],
+    keys: &'static str,
+    desc: &'static str,
+    action: Action,
+) -> Binding {
+    Binding {
+        ctrl: true,
+        ..bind(codes, keys, desc, action)
+    }
+}
+
+/// the help on the screens that take typed text, where `?` is just another
+/// character
+const TEXT_HELP: Binding = bind(&[F(1)], "F1", "Help", Action::Help);
+
+/// the help on the other screens
+const HELP: Binding = bind(&[Char('?')], "?", "Help", Action::Help);
+
+/// going back from a screen that takes no typed text, where Backspace can go
+/// back too
+const BACK: Binding = bind(&[Esc, Backspace], "Esc", "Back", Action::Back);
+
+const MOVE: Binding = bind(&[Up, Down], "↑/↓", "Move", Action::Select);
+
+const SEARCH_MODE: Binding =
+    ctrl(&[Char('t')], "C-t", "Search", Action::SearchMode);
+
+/// the bindings of the main screen, in the order they are listed in the help
+pub const MAIN: &[Binding] = &[
+    bind(&[Char('?')], "?", "Help", Action::Help),
+    bind(&[Char('q')], "q", "Quit", Action::Quit),
+    bind(&[Char('a')], "a", "Add Food", Action::AddFood),
+    bind(&[Char('/')], "/", "Search", Action::Search),
+    bind(&[Char('e')], "e", "Quick Add", Action::QuickAdd),
+    bind(&[Char('N')], "N", "Numpad", Action::Numpad),
+    bind(&[Char('s')], "s", "Split Dish", Action::SplitDish),
+    bind(&[Char('c')], "c", "Copy Day", Action::CopyDay),
+    bind(&[Char('C')], "C", "Calendar", Action::Calendar),
+    bind(&[Char('x')], "x", "Compare", Action::Compare),
+    bind(&[Left, Right], "←/→", "Day", Action::Day),
+    bind(&[Esc], "Esc", "Today", Action::Today),
+    bind(&[Char('R')], "R", "Reports", Action::Reports),
+    bind(&[Char('t')], "t", "Time", Action::Time),
+    bind(
+        &[Char('+'), Char('='), Char('-')],
+        "+/-",
+        "Quantity",
+        Action::Quantity,
+    ),
+    bind(&[Char('w')], "w", "Weigh", Action::Weigh),
+    bind(&[Char('r')], "r", "Repeat", Action::Repeat),
+    bind(&[Char('u')], "u", "Undo", Action::Undo),
+    bind(&[Char('~')], "~", "Estimate", Action::Estimate),
+    bind(&[Char('p')], "p", "Partial", Action::Partial),
+    bind(&[Char('m')], "m", "Note", Action::Note),
+    bind(&[Char('M')], "M", "Day Note", Action::DayNote),
+    bind(&[Char('g')], "g", "Day Goals", Action::DayGoals),
+    bind(&[Char('E')], "E", "Event", Action::Event),
+    bind(&[Char('i')], "i", "Inbox", Action::Inbox),
+    bind(&[Char('v')], "v", "Review", Action::Review),
+    bind(&[Char('h')], "h", "History", Action::History),
+    bind(&[Char('f')], "f", "Compare Foods", Action::CompareFoods),
+    bind(
+        &[
+            Char('1'),
+            Char('2'),
+            Char('3'),
+            Char('4'),
+            Char('5'),
+            Char('6'),
+            Char('7'),
+            Char('8'),
+            Char('9'),
+        ],
+        "1-9",
+        "Recent",
+        Action::Recent,
+    ),
+    bind(&[Up, Down], "↑/↓", "Select", Action::Select),
+    bind(&[Tab], "Tab", "Pane", Action::Pane),
+    bind(&[Enter], "Ret", "Log Recent", Action::LogRecent),
+];
+
+/// the bindings of the AddFood and SplitDish forms
+pub const FORM: &[Binding] = &[
+    TEXT_HELP,
+    bind(&[Tab, Enter], "Tab/Ret", "Next", Action::Next),
+    bind(&[BackTab], "S-Tab", "Prev", Action::Prev),
+    bind(&[Up, Down], "↑/↓", "Pick or recall", Action::Select),
+    SEARCH_MODE,
+    ctrl(&[Char('n')], "C-n", "Queue", Action::Queue),
+    bind(&[PageUp, PageDown], "PgUp/PgDn", "Date", Action::Day),
+    ctrl(&[Char('s')], "C-s", "Submit", Action::Submit),
+    bind(&[Esc], "Esc", "Cancel", Action::Back),
+];
+
+pub const QUICK_ADD: &[Binding] = &[
+    TEXT_HELP,
+    bind(&[Enter], "Ret", "Submit", Action::Accept),
+    bind(&[Esc], "Esc", "Cancel", Action::Back),
+];
+
+pub const PROMPT: &[Binding] = &[
+    TEXT_HELP,
+    bind(&[Enter], "Ret", "Accept", Action::Accept),
+    bind(&[Tab], "Tab", "Scale", Action::Scale),
+    bind(&[Esc], "Esc", "Cancel", Action::Back),
+];
+
+pub const REPORTS: &[Binding] = &[
+    HELP,
+    MOVE,
+    bind(&[Enter], "Ret", "Open", Action::Accept),
+    BACK,
+];
+
+/// the bindings of the Report screen, of which only one of Period, Month, and
+/// Year applies to any report
+pub const REPORT: &[Binding] = &[
+    HELP,
+    bind(&[Up, Down], "↑/↓", "Scroll", Action::Select),
+    bind(&[Left, Right], "←/→", "Period", Action::Period),
+    bind(&[Left, Right], "←/→", "Month", Action::Month),
+    bind(&[Left, Right], "←/→", "Year", Action::Year),
+    bind(&[Esc, Backspace, Char('q')], "Esc", "Back", Action::Back),
+];
+
+pub const INBOX: &[Binding] = &[
+    HELP,
+    MOVE,
+    bind(&[Enter], "Ret", "Resolve", Action::Accept),
+    bind(&[Char('d')], "d", "Delete", Action::Delete),
+    BACK,
+];
+
+pub const REVIEW: &[Binding] = &[
+    HELP,
+    MOVE,
+    bind(&[Enter], "Ret", "Go To", Action::Accept),
+    BACK,
+];
+
+pub const CALENDAR: &[Binding] = &[
+    HELP,
+    bind(&[Left, Right, Up, Down], "←/→/↑/↓", "Move", Action::Select),
+    bind(&[PageUp, PageDown], "PgUp/PgDn", "Month", Action::Month),
+    bind(&[Enter], "Ret", "Go To", Action::Accept),
+    BACK,
+];
+
+pub const WEIGH: &[Binding] = &[
+    TEXT_HELP,
+    bind(&[Enter], "Ret", "Next", Action::Accept),
+    bind(&[Tab], "Tab", "Scale", Action::Scale),
+    MOVE,
+    bind(&[Esc], "Esc", "Done", Action::Back),
+];
+
+/// the bindings of the Numpad screen, which only takes digits, so `?` is free
+pub const NUMPAD: &[Binding] = &[
+    HELP,
+    bind(&[Enter], "Ret", "Next/Log", Action::Accept),
+    bind(&[Char('.')], ".", "Next", Action::Next),
+    bind(&[Esc], "Esc", "Done", Action::Back),
+];
+
+pub const COMPARE: &[Binding] = &[
+    HELP,
+    bind(&[Up, Down], "↑/↓", "Left Day", Action::Select),
+    bind(&[Left, Right], "←/→", "Right Day", Action::Day),
+    BACK,
+];
+
+pub const HISTORY: &[Binding] = &[
+    TEXT_HELP,
+    MOVE,
+    bind(&[Enter], "Ret", "Go To", Action::Accept),
+    SEARCH_MODE,
+    bind(&[Esc], "Esc", "Back", Action::Back),
+];
+
+pub const FOOD_COMPARE: &[Binding] = &[
+    TEXT_HELP,
+    MOVE,
+    bind(&[Tab, BackTab], "Tab", "Other Food", Action::Pane),
+    SEARCH_MODE,
+    bind(&[Esc], "Esc", "Back", Action::Back),
+];
+
+pub const SEARCH: &[Binding] = &[
+    TEXT_HELP,
+    MOVE,
+    bind(&[Enter], "Ret", "Quantity", Action::Accept),
+    SEARCH_MODE,
+    bind(&[Esc], "Esc", "Back", Action::Back),
+];
+
+pub const COPY_DAY: &[Binding] = &[
+    HELP,
+    bind(&[Left, Right], "←/→", "Day", Action::Day),
+    MOVE,
+    bind(&[Char(' ')], "Spc", "Toggle", Action::Toggle),
+    bind(&[Enter], "Ret", "Copy", Action::Accept),
+    bind(&[Esc, Backspace], "Esc", "Cancel", Action::Back),
+];
+
+/// the bindings of the help itself, which closes on any of them
+pub const HELP_OVERLAY: &[Binding] = &[bind(
+    &[Esc, Enter, Backspace, Char('?'), Char('q'), F(1)],
+    "Esc",
+    "Close",
+    Action::Back,
+)];
+
+/// the bindings added by the `vim` setting, which take the place of any others
+/// on the same keys
+pub const VIM: &[Binding] = &[
+    bind(&[Char('j'), Char('k')], "j/k", "Select", Action::Select),
+    bind(&[Char('h'), Char('l')], "h/l", "Day", Action::Day),
+    bind(&[Char(':')], ":", "Command", Action::Command),
+];
+
+/// the bindings of the main screen, with the [VIM] ones first if `vim`. any
+/// binding left without keys of its own is dropped
+pub fn main(vim: bool) -> Vec<&'static Binding> {
+    let vim = if vim { VIM } else { &[] };
+    let taken = |code| vim.iter().any(|binding| binding.codes.contains(code));
+    vim.iter()
+        .chain(
+            MAIN.iter()
+                .filter(|binding| !binding.codes.iter().all(taken)),
+        )
+        .collect()
+}
+
+/// the action of the first of `bindings` on the key of `event`, if any
+pub fn find(bindings: &[&Binding], event: KeyEvent) -> Option<Action> {
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    bindings
+        .iter()
+        .find(|binding| {
+            binding.codes.contains(&event.code) && (ctrl || !binding.ctrl)
+        })
+        .map(|binding| binding.action)
+}
+
+/// the arrow key that `code` stands for with vim keys, or `code` itself
+pub fn vim_arrow(code: KeyCode) -> KeyCode {
+    match code {
+        Char('h') => Left,
+        Char('j') => Down,
+        Char('k') => Up,
+        Char('l') => Right,
+        code => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_screen_has_help_and_back() {
+        let tables = [
+            MAIN,
+            FORM,
+            QUICK_ADD,
+            PROMPT,
+            REPORTS,
+            REPORT,
+            INBOX,
+            REVIEW,
+            CALENDAR,
+            WEIGH,
+            NUMPAD,
+            COMPARE,
+            HISTORY,
+            FOOD_COMPARE,
+            SEARCH,
+            COPY_DAY,
+        ];
+        for table in tables {
+            let has = |action| table.iter().any(|b| b.action == action);
+            assert!(has(Action::Help));
+            assert!(has(Action::Back) || has(Action::Today));
+        }
+    }
+
+    #[test]
+    fn ctrl_bindings_need_ctrl() {
+        let form: Vec<&Binding> = FORM.iter().collect();
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        let plain = key(Char('n'), KeyModifiers::NONE);
+        assert!(find(&form, plain).is_none());
+        let queue = key(Char('n'), KeyModifiers::CONTROL);
+        assert!(find(&form, queue) == Some(Action::Queue));
+    }
+}
@@ -0,0 +1,286 @@
+//! rectangles of the screen and constraints for splitting them, so that the
+//! screens can be laid out by their parts instead of by raw coordinates, and
+//! the fitting of text into them
+
+use unicode_width::UnicodeWidthChar;
+
+/// a rectangle of cells with its top left corner at (x, y)
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// which way a [Rect] is split
+#[derive(Clone, Copy)]
+pub enum Direction {
+    /// into columns, left to right
+    Horizontal,
+    /// into rows, top to bottom
+    Vertical,
+}
+
+/// how much of a split [Rect] one part takes
+#[derive(Clone, Copy)]
+pub enum Constraint {
+    /// this many cells, or whatever is left if there are fewer
+    Length(u16),
+    /// an equal share of whatever the other parts leave
+    Fill,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// the rectangle left after taking `h` columns from each side and `v` rows
+    /// from the top and bottom
+    pub fn inner(self, h: u16, v: u16) -> Self {
+        Self {
+            x: self.x + h,
+            y: self.y + v,
+            width: self.width.saturating_sub(2 * h),
+            height: self.height.saturating_sub(2 * v),
+        }
+    }
+
+    /// the last row of the rectangle
+    pub fn bottom(self) -> u16 {
+        (self.y + self.height).saturating_sub(1)
+    }
+
+    /// the last column of the rectangle
+    pub fn right(self) -> u16 {
+        (self.x + self.width).saturating_sub(1)
+    }
+
+    /// a rectangle of `width` by `height` centered in this one, shrunk to fit
+    pub fn centered(self, width: u16, height: u16) -> Self {
+        let (width, height) = (width.min(self.width), height.min(self.height));
+        Self {
+            x: self.x + (self.width - width) / 2,
+            y: self.y + (self.height - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// split the rectangle into adjacent parts in `direction`. the lengths are
+    /// taken first, in order, and the parts to [Fill] share the rest, with any
+    /// odd cells going to the last of them
+    pub fn split<const N: usize>(
+        self,
+        direction: Direction,
+        constraints: [Constraint; N],
+    ) -> [Rect; N] {
+        let mut left = match direction {
+            Direction::Horizontal => self.width,
+            Direction::Vertical => self.height,
+        };
+        let mut sizes = [0; N];
+        for (size, c) in sizes.iter_mut().zip(constraints) {
+            let want = match c {
+                Constraint::Length(n) => n,
+                Constraint::Fill => continue,
+            };
+            *size = want.min(left);
+            left -= *size;
+        }
+        let fills: Vec<usize> = (0..N)
+            .filter(|&i| matches!(constraints[i], Constraint::Fill))
+            .collect();
+        if let Some(&last) = fills.last() {
+            let share = left / fills.len() as u16;
+            for &i in &fills {
+                sizes[i] = share;
+            }
+            sizes[last] += left - share * fills.len() as u16;
+        }
+        let mut start = match direction {
+            Direction::Horizontal => self.x,
+            Direction::Vertical => self.y,
+        };
+        sizes.map(|size| {
+            let part = match direction {
+                Direction::Horizontal => Rect {
+                    x: start,
+                    width: size,
+                    ..self
+                },
+                Direction::Vertical => Rect {
+                    y: start,
+                    height: size,
+                    ..self
+                },
+            };
+            start += size;
+            part
+        })
+    }
+}
+
+/// the characters that boxes and gauges are drawn with
+pub struct Border {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_right: char,
+    pub bottom_left: char,
+    pub bottom_right: char,
+    /// the joins of a horizontal line into the left and right sides
+    pub left_tee: char,
+    pub right_tee: char,
+    /// the filled and empty parts of a gauge
+    pub full: char,
+    pub empty: char,
+}
+
+impl Border {
+    /// unicode light box drawing characters
+    pub const LIGHT: Border = Border {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_right: '┐',
+        bottom_left: '└',
+        bottom_right: '┘',
+        left_tee: '├',
+        right_tee: '┤',
+        full: '█',
+        empty: '░',
+    };
+
+    /// plain ASCII, for terminals and fonts without box drawing characters
+    pub const ASCII: Border = Border {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_right: '+',
+        bottom_left: '+',
+        bottom_right: '+',
+        left_tee: '+',
+        right_tee: '+',
+        full: '#',
+        empty: '.',
+    };
+
+    /// [Border::ASCII] if `ascii` or, when that is unset, if the locale does
+    /// not use UTF-8, and [Border::LIGHT] otherwise
+    pub fn new(ascii: Option<bool>) -> &'static Border {
+        let ascii = ascii.unwrap_or_else(|| {
+            let locale =
+                ["LC_ALL", "LC_CTYPE", "LANG"].iter().find_map(|var| {
+                    std::env::var(var).ok().filter(|s| !s.is_empty())
+                });
+            locale.is_some_and(|locale| {
+                !locale.to_lowercase().replace('-', "").contains("utf8")
+            })
+        });
+        if ascii {
+            &Self::ASCII
+        } else {
+            &Self::LIGHT
+        }
+    }
+
+    /// a horizontal line `width` long
+    pub fn line(&self, width: usize) -> String {
+        self.horizontal.to_string().repeat(width)
+    }
+
+    /// a gauge `width` long filled to `fraction`, which is clamped to between
+    /// 0 and 1
+    pub fn gauge(&self, fraction: f64, width: usize) -> String {
+        let full = (fraction.clamp(0.0, 1.0) * width as f64).round() as usize;
+        let full = self.full.to_string().repeat(full);
+        let empty = self.empty.to_string().repeat(width - full.chars().count());
+        full + &empty
+    }
+}
+
+/// the columns of the terminal taken up by `s`, counting wide characters as
+/// two columns and combining ones as none. this adds up the characters one at
+/// a time, which can differ from the width of the whole string for sequences
+/// like emoji with presentation selectors
+fn char_width(s: &str) -> usize {
+    s.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+/// the longest start of `s` that fits in `width` columns of the terminal, by
+/// [char_width]
+pub fn truncate(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in s.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+/// `s` cut off or padded with spaces to fill exactly `width` columns
+pub fn fit(s: &str, width: usize) -> String {
+    let s = truncate(s, width);
+    format!("{s}{}", " ".repeat(width.saturating_sub(char_width(s))))
+}
+
+/// `s` [fit] to `width` columns with the cursor `cursor` columns into it,
+/// dropping as much of its start as it takes to keep the cursor inside them.
+/// returns the text and the column of the cursor in it
+pub fn scroll(s: &str, cursor: usize, width: usize) -> (String, usize) {
+    let skip = cursor.saturating_sub(width.saturating_sub(1));
+    let mut skipped = 0;
+    let start = s
+        .char_indices()
+        .find(|&(_, c)| {
+            if skipped >= skip {
+                return true;
+            }
+            skipped += c.width().unwrap_or(0);
+            false
+        })
+        .map_or(s.len(), |(i, _)| i);
+    let cursor = cursor.saturating_sub(skipped);
+    (fit(&s[start..], width), cursor.min(width.saturating_sub(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_emoji_with_selector() {
+        // two columns as a string but one counted by character
+        let heart = "\u{2764}\u{FE0F}";
+        for width in 0..4 {
+            let s = fit(&format!("ab{heart}"), width);
+            assert_eq!(char_width(&s), width);
+        }
+    }
+
+    #[test]
+    fn fit_pads_and_truncates() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 3), "abc");
+        assert_eq!(fit("日本", 3), "日 ");
+    }
+
+    #[test]
+    fn scroll_keeps_cursor_inside() {
+        assert_eq!(scroll("abc", 3, 5), ("abc  ".to_owned(), 3));
+        assert_eq!(scroll("abcdef", 6, 4), ("def ".to_owned(), 3));
+        assert_eq!(scroll("abcdef", 2, 4), ("abcd".to_owned(), 2));
+        assert_eq!(scroll("abcdef", 5, 4), ("cdef".to_owned(), 3));
+        // a wide character is dropped whole
+        assert_eq!(scroll("日本語", 6, 4), ("語  ".to_owned(), 2));
+    }
+}
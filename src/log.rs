@@ -0,0 +1,658 @@
+//! the daily food log
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    fmt::Display,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use chrono::{NaiveDate, NaiveTime};
+
+use crate::{search::Query, Food, Macros};
+
+/// the format used for dates in the log file
+pub const DATE_FMT: &str = "%Y-%m-%d";
+
+/// the format used for the time of day an entry was logged
+pub const TIME_FMT: &str = "%H:%M";
+
+/// the comment written at the top of the log file
+const HEADER: &str = "# tsv file with the following columns
+# Date [Time]\tQuantity\tFood Name\tCalories\tCarbs\tFat\tProtein\tUnit
+# where the quantity may end in `@` and the fraction of it actually eaten,
+# followed by any optional nutrients recorded for the food and an optional
+# trailing note after a tab and `# `. a line with only a date and a note is a
+# note for the whole day
+";
+
+/// the separator between a quantity and the fraction of it eaten
+const CONSUMED_SEP: char = '@';
+
+/// the separator between a line and its trailing note
+pub const NOTE_SEP: &str = "\t# ";
+
+/// the start of a journal record, followed by a tab and the date whose entries
+/// and note are replaced by the lines of the record
+const RECORD_START: &str = "=\t";
+
+/// the line ending a journal record. a record without it was cut off partway
+/// through writing and is ignored
+const RECORD_END: &str = ".";
+
+/// the number of journal records after which they are compacted into the log
+/// file
+const COMPACT_AFTER: usize = 100;
+
+/// read the file at `path`, treating a missing file as empty
+fn read(path: &Path) -> io::Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => Ok(s),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// a complete record in a journal, with the offset of its first line
+struct Record<'a> {
+    start: usize,
+    date: NaiveDate,
+    lines: Vec<&'a str>,
+}
+
+/// the complete records in the journal `s`, in the order they were written
+fn records(s: &str) -> Vec<Record<'_>> {
+    let mut ret = Vec::new();
+    let mut record: Option<Record> = None;
+    let mut start = 0;
+    for line in s.split_inclusive('\n') {
+        let offset = start;
+        start += line.len();
+        let line = line.trim_end_matches('\n');
+        if let Some(date) = line.strip_prefix(RECORD_START) {
+            record =
+                NaiveDate::parse_from_str(date, DATE_FMT).ok().map(|date| {
+                    Record {
+                        start: offset,
+                        date,
+                        lines: Vec::new(),
+                    }
+                });
+        } else if line == RECORD_END {
+            ret.extend(record.take());
+        } else if let Some(record) = &mut record {
+            record.lines.push(line);
+        }
+    }
+    ret
+}
+
+/// a single logged food and the quantity eaten, in units of `food.unit`
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub food: Food,
+    pub quantity: f64,
+    /// the time of day the entry was eaten, if known
+    pub time: Option<NaiveTime>,
+    /// whether the quantity or macros are only a rough estimate. this is
+    /// stored as a `~` before the quantity in the log file
+    pub estimated: bool,
+    /// a free-text note about the entry
+    pub note: Option<String>,
+    /// the fraction of the quantity actually eaten, for food that was logged
+    /// but not finished
+    pub consumed: f64,
+}
+
+impl Entry {
+    pub fn new(food: Food, quantity: f64) -> Self {
+        Self {
+            food,
+            quantity,
+            time: None,
+            estimated: false,
+            note: None,
+            consumed: 1.0,
+        }
+    }
+
+    /// format the entry as a line of the log file, logged on `date`
+    pub fn to_line(&self, date: NaiveDate) -> String {
+        let mut stamp = date.format(DATE_FMT).to_string();
+        if let Some(time) = self.time {
+            stamp = format!("{stamp} {}", time.format(TIME_FMT));
+        }
+        let mut s =
+            format!("{stamp}\t{}\t{}", self.format_quantity(), self.food);
+        if let Some(note) = &self.note {
+            s.push_str(&format!("{NOTE_SEP}{}", one_line(note)));
+        }
+        s
+    }
+
+    /// the quantity actually eaten
+    pub fn eaten(&self) -> f64 {
+        self.quantity * self.consumed
+    }
+
+    /// the quantity as written in the log file, with any estimate marker and
+    /// fraction eaten
+    fn format_quantity(&self) -> String {
+        let est = if self.estimated { "~" } else { "" };
+        if self.consumed == 1.0 {
+            format!("{est}{}", self.quantity)
+        } else {
+            format!("{est}{}{CONSUMED_SEP}{}", self.quantity, self.consumed)
+        }
+    }
+
+    /// the macros contributed by this entry
+    pub fn macros(&self) -> Macros {
+        let mut ret = Macros::default();
+        ret += self.food.clone() * self.eaten();
+        ret
+    }
+
+    /// the value of `name` for this entry, for use in formulas: `calories`,
+    /// `protein`, `carbs`, `fat`, or one of the optional nutrients in lower
+    /// case. unknown nutrients count as zero
+    pub fn variable(&self, name: &str) -> Option<f64> {
+        Some(self.food.variable(name)? * self.eaten())
+    }
+
+    /// reasons this entry looks wrong, if any
+    pub fn problems(&self) -> Vec<&'static str> {
+        let mut ret = Vec::new();
+        if self.quantity <= 0.0 {
+            ret.push("quantity is not positive");
+        }
+        if self.consumed <= 0.0 || self.consumed > 1.0 {
+            ret.push("fraction eaten is not between 0 and 1");
+        }
+        // calories should roughly match 4 kcal/g of protein and carbs and 9
+        // kcal/g of fat, with some allowance for fiber, alcohol, and rounding
+        let food = &self.food;
+        let expected = 4.0 * (food.protein + food.carbs) + 9.0 * food.fat;
+        if (expected - food.calories).abs() > f64::max(20.0, 0.2 * expected) {
+            ret.push("calories do not match macros");
+        }
+        ret
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.estimated {
+            write!(f, "~")?;
+        }
+        write!(f, "{} {} {}", self.quantity, self.food.unit, self.food.name)?;
+        if self.consumed != 1.0 {
+            write!(f, " ({:.0}% eaten)", 100.0 * self.consumed)?;
+        }
+        write!(f, " ({:.0} kcal)", self.macros().calories)
+    }
+}
+
+/// replace the tabs and newlines in `note` that would break the log format
+fn one_line(note: &str) -> String {
+    note.replace(['\t', '\n'], " ")
+}
+
+/// a line in the log file: either an entry, consisting of the date and
+/// optional time, the quantity, and the fields of a [Food], or a note for a
+/// whole day
+enum Line {
+    Entry(NaiveDate, Box<Entry>),
+    DayNote(NaiveDate, String),
+}
+
+impl FromStr for Line {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, note) = match s.split_once(NOTE_SEP) {
+            Some((s, note)) => (s, Some(note.to_owned())),
+            None => (s, None),
+        };
+        if let Some(note) = &note {
+            if let Ok(date) = NaiveDate::parse_from_str(s, DATE_FMT) {
+                return Ok(Self::DayNote(date, note.clone()));
+            }
+        }
+        let fields: Vec<&str> = s.split('\t').collect();
+        if fields.len() < 8 {
+            Err("invalid field number")?;
+        }
+        let (date, time) = match fields[0].split_once(' ') {
+            Some((date, time)) => {
+                (date, Some(NaiveTime::parse_from_str(time, TIME_FMT)?))
+            }
+            None => (fields[0], None),
+        };
+        let date = NaiveDate::parse_from_str(date, DATE_FMT)?;
+        let (estimated, quantity) = match fields[1].strip_prefix('~') {
+            Some(q) => (true, q),
+            None => (false, fields[1]),
+        };
+        let (quantity, consumed) = match quantity.split_once(CONSUMED_SEP) {
+            Some((q, c)) => (q, c.parse()?),
+            None => (quantity, 1.0),
+        };
+        let quantity = quantity.parse()?;
+        let food = fields[2..].join("\t").parse()?;
+        let mut entry = Entry::new(food, quantity);
+        entry.consumed = consumed;
+        entry.time = time;
+        entry.estimated = estimated;
+        entry.note = note;
+        Ok(Self::Entry(date, Box::new(entry)))
+    }
+}
+
+/// how often and how recently a food has been logged
+#[derive(Clone, Copy)]
+pub struct Usage {
+    pub count: usize,
+    pub last: NaiveDate,
+}
+
+/// all of the logged entries, grouped by day and kept in time order within each
+/// day. the macros of each food are stored alongside the entry so that later
+/// changes to the food database do not rewrite history.
+///
+/// changes are not written to the log file directly but appended to a journal
+/// beside it, as records replacing the contents of a single day. this keeps a
+/// crash from losing anything but the change being written, and lets the last
+/// changes be undone. once the journal grows long enough, it is compacted into
+/// the log file
+pub struct Log {
+    path: PathBuf,
+    days: BTreeMap<NaiveDate, Vec<Entry>>,
+    /// free-text notes about whole days
+    notes: BTreeMap<NaiveDate, String>,
+    /// the lines of each day as last written to disk, for finding the days
+    /// that changed since
+    saved: BTreeMap<NaiveDate, String>,
+    /// the number of records in the journal
+    records: usize,
+    /// the lines of the log file and journal that could not be read, kept
+    /// as they are so that compacting doesn't lose them
+    invalid: Vec<String>,
+}
+
+impl Log {
+    /// load the log from `path` and apply the changes in its journal. missing
+    /// files are treated as empty
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut ret = Self {
+            path: path.as_ref().to_owned(),
+            days: BTreeMap::new(),
+            notes: BTreeMap::new(),
+            saved: BTreeMap::new(),
+            records: 0,
+            invalid: Vec::new(),
+        };
+        for line in read(&ret.path)?.lines() {
+            ret.insert(line);
+        }
+        let journal = read(&ret.journal())?;
+        let records = records(&journal);
+        for record in &records {
+            ret.days.remove(&record.date);
+            ret.notes.remove(&record.date);
+            for line in &record.lines {
+                ret.insert(line);
+            }
+        }
+        ret.records = records.len();
+        for entries in ret.days.values_mut() {
+            entries.sort_by_key(|e| e.time);
+        }
+        ret.saved = ret
+            .dates()
+            .into_iter()
+            .map(|date| (date, ret.day_lines(date)))
+            .collect();
+        Ok(ret)
+    }
+
+    /// add the entry or note on `line` of the log file, ignoring comments and
+    /// blank lines. lines that cannot be read are set aside in
+    /// [Log::invalid]
+    fn insert(&mut self, line: &str) {
+        if line.starts_with('#') || line.trim().is_empty() {
+            return;
+        }
+        match line.parse() {
+            Ok(Line::Entry(date, entry)) => {
+                self.days.entry(date).or_default().push(*entry)
+            }
+            Ok(Line::DayNote(date, note)) => {
+                self.notes.insert(date, note);
+            }
+            Err(_) => self.invalid.push(line.to_owned()),
+        }
+    }
+
+    /// the lines of the log file and journal that could not be read
+    pub fn invalid(&self) -> &[String] {
+        &self.invalid
+    }
+
+    /// the path of the journal of changes not yet compacted into the log file
+    fn journal(&self) -> PathBuf {
+        self.path.with_extension("journal")
+    }
+
+    /// the dates with entries or notes
+    fn dates(&self) -> BTreeSet<NaiveDate> {
+        self.days.keys().chain(self.notes.keys()).copied().collect()
+    }
+
+    /// the lines of the log file holding the note and entries of `date`
+    fn day_lines(&self, date: NaiveDate) -> String {
+        let mut s = String::new();
+        if let Some(note) = self.notes.get(&date) {
+            s.push_str(&format!(
+                "{}{NOTE_SEP}{}\n",
+                date.format(DATE_FMT),
+                one_line(note)
+            ));
+        }
+        for entry in self.day(date) {
+            s.push_str(&entry.to_line(date));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// append a journal record for each day changed since the last save,
+    /// compacting the journal if it has grown too long
+    pub fn save(&mut self) -> io::Result<()> {
+        let mut dates = self.dates();
+        dates.extend(self.saved.keys());
+        let mut s = String::new();
+        for date in dates {
+            let lines = self.day_lines(date);
+            if self.saved.get(&date).map_or("", String::as_str) == lines {
+                continue;
+            }
+            s.push_str(&format!("{RECORD_START}{}\n", date.format(DATE_FMT)));
+            s.push_str(&lines);
+            s.push_str(RECORD_END);
+            s.push('\n');
+            self.records += 1;
+            if lines.is_empty() {
+                self.saved.remove(&date);
+            } else {
+                self.saved.insert(date, lines);
+            }
+        }
+        if s.is_empty() {
+            return Ok(());
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.journal())?
+            .write_all(s.as_bytes())?;
+        if self.records > COMPACT_AFTER {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// write the whole log to the log file and clear the journal. this gives
+    /// up the ability to undo the changes in the journal. lines that could not
+    /// be read are written back unchanged after the header
+    pub fn compact(&mut self) -> io::Result<()> {
+        let mut s = String::from(HEADER);
+        for line in &self.invalid {
+            s.push_str(line);
+            s.push('\n');
+        }
+        for date in self.dates() {
+            s.push_str(&self.day_lines(date));
+        }
+        // replace the log file in one step so that a crash leaves either the
+        // old or the new one. replaying the journal over the new one changes
+        // nothing, so a crash before the journal is removed is also harmless
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, s)?;
+        std::fs::rename(&tmp, &self.path)?;
+        match std::fs::remove_file(self.journal()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+        self.records = 0;
+        Ok(())
+    }
+
+    /// drop the last record from the journal and reload the log, undoing the
+    /// change it made to a day. this returns false if there was nothing to
+    /// undo
+    pub fn undo(&mut self) -> io::Result<bool> {
+        let journal = read(&self.journal())?;
+        let Some(last) = records(&journal).pop() else {
+            return Ok(false);
+        };
+        std::fs::write(self.journal(), &journal[..last.start])?;
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// the last time the log file or its journal was modified, if either
+    /// exists
+    pub fn modified(&self) -> Option<SystemTime> {
+        [self.path.clone(), self.journal()]
+            .iter()
+            .filter_map(|path| std::fs::metadata(path).ok()?.modified().ok())
+            .max()
+    }
+
+    /// load the log again from its file, picking up changes made elsewhere
+    pub fn reload(&mut self) -> io::Result<()> {
+        *self = Self::load(&self.path)?;
+        Ok(())
+    }
+
+    /// the first and last dates with entries or notes, if any
+    pub fn span(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let dates = || self.days.keys().chain(self.notes.keys());
+        Some((*dates().min()?, *dates().max()?))
+    }
+
+    /// the note for the whole of `date`, if any
+    pub fn day_note(&self, date: NaiveDate) -> Option<&str> {
+        self.notes.get(&date).map(String::as_str)
+    }
+
+    /// set or, if `note` is empty, clear the note for `date`
+    pub fn set_day_note(&mut self, date: NaiveDate, note: String) {
+        if note.is_empty() {
+            self.notes.remove(&date);
+        } else {
+            self.notes.insert(date, note);
+        }
+    }
+
+    /// the entries logged on `date`
+    pub fn day(&self, date: NaiveDate) -> &[Entry] {
+        self.days.get(&date).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// iterate over the days from `from` to `to`, inclusive, that have entries
+    pub fn range(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = (NaiveDate, &[Entry])> {
+        self.days.range(from..=to).map(|(d, e)| (*d, e.as_slice()))
+    }
+
+    /// add `entry` to `date`, keeping the day in time order, and return its
+    /// index within the day
+    pub fn add(&mut self, date: NaiveDate, entry: Entry) -> usize {
+        let entries = self.days.entry(date).or_default();
+        let i = entries.partition_point(|e| e.time <= entry.time);
+        entries.insert(i, entry);
+        i
+    }
+
+    /// a mutable reference to the `i`th entry on `date`. changing the entry's
+    /// time through this reference will break the ordering of the day, so use
+    /// [Log::set_time] for that
+    pub fn get_mut(&mut self, date: NaiveDate, i: usize) -> Option<&mut Entry> {
+        self.days.get_mut(&date)?.get_mut(i)
+    }
+
+    /// remove and return the `i`th entry on `date`
+    pub fn remove(&mut self, date: NaiveDate, i: usize) -> Entry {
+        let entries = self.days.get_mut(&date).unwrap();
+        let ret = entries.remove(i);
+        if entries.is_empty() {
+            self.days.remove(&date);
+        }
+        ret
+    }
+
+    /// change the time of the `i`th entry on `date`, returning its new index
+    pub fn set_time(
+        &mut self,
+        date: NaiveDate,
+        i: usize,
+        time: NaiveTime,
+    ) -> usize {
+        let mut entry = self.remove(date, i);
+        entry.time = Some(time);
+        self.add(date, entry)
+    }
+
+    /// the last `n` distinct foods logged, most recent first
+    pub fn recent_foods(&self, n: usize) -> Vec<&Food> {
+        let mut ret: Vec<&Food> = Vec::new();
+        let entries = self.days.values().rev().flat_map(|day| day.iter().rev());
+        for entry in entries {
+            if ret.len() == n {
+                break;
+            }
+            if !ret.iter().any(|f| f.name == entry.food.name) {
+                ret.push(&entry.food);
+            }
+        }
+        ret
+    }
+
+    /// how many times each food has been logged and the last day it was
+    pub fn usage(&self) -> HashMap<String, Usage> {
+        let mut ret: HashMap<String, Usage> = HashMap::new();
+        for (date, day) in &self.days {
+            for entry in day {
+                let name = entry.food.name.clone();
+                let usage = ret.entry(name).or_insert(Usage {
+                    count: 0,
+                    last: *date,
+                });
+                usage.count += 1;
+                usage.last = *date;
+            }
+        }
+        ret
+    }
+
+    /// the quantity of `food` logged most often in its current unit, preferring
+    /// the most recent of equally common ones, or None if it was never logged
+    pub fn usual_quantity(&self, food: &Food) -> Option<f64> {
+        // each quantity by its bits, with its count and when it was last seen
+        let mut counts: HashMap<u64, (usize, usize)> = HashMap::new();
+        let entries =
+            self.days.values().flatten().filter(|e| {
+                e.food.name == food.name && e.food.unit == food.unit
+            });
+        for (i, entry) in entries.enumerate() {
+            let count = counts.entry(entry.quantity.to_bits()).or_default();
+            *count = (count.0 + 1, i);
+        }
+        counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(bits, _)| f64::from_bits(bits))
+    }
+
+    /// the entries of foods whose names match `query`, with their dates and
+    /// indices within the day, most recent first
+    pub fn search(&self, query: &Query) -> Vec<(NaiveDate, usize, &Entry)> {
+        self.days
+            .iter()
+            .rev()
+            .flat_map(|(date, day)| {
+                day.iter().enumerate().rev().map(|(i, e)| (*date, i, e))
+            })
+            .filter(|(_, _, e)| query.score(&e.food.name).is_some())
+            .collect()
+    }
+
+    /// the total macros eaten on `date`
+    pub fn totals(&self, date: NaiveDate) -> Macros {
+        let mut ret = Macros::default();
+        for entry in self.day(date) {
+            ret += entry.macros();
+        }
+        ret
+    }
+
+    /// the average of the daily totals over the days from `from` to `to` with
+    /// entries, or None if there are none
+    pub fn average(&self, from: NaiveDate, to: NaiveDate) -> Option<Macros> {
+        let mut ret = Macros::default();
+        let mut days = 0;
+        for (date, entries) in self.range(from, to) {
+            if !entries.is_empty() {
+                ret += self.totals(date);
+                days += 1;
+            }
+        }
+        if days == 0 {
+            return None;
+        }
+        let d = days as f64;
+        Some(Macros {
+            calories: ret.calories / d,
+            carbs: ret.carbs / d,
+            fat: ret.fat / d,
+            protein: ret.protein / d,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_keeps_invalid_lines() {
+        let dir = std::env::temp_dir()
+            .join(format!("macroni-log-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("entries");
+        let valid = "2024-01-01\t2\tBun\t140\t25\t2\t4\tbun";
+        let invalid = "2024-01-02\tnot an entry";
+        let lines = format!("{valid}\n{invalid}\n{invalid}\n");
+        std::fs::write(&path, lines).unwrap();
+
+        let mut log = Log::load(&path).unwrap();
+        assert_eq!(log.invalid(), [invalid, invalid]);
+        log.compact().unwrap();
+        // compacting again writes the same lines, without doubling them
+        let mut log = Log::load(&path).unwrap();
+        log.compact().unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(written.lines().filter(|&line| line == valid).count(), 1);
+        assert_eq!(written.lines().filter(|&line| line == invalid).count(), 2);
+    }
+}
@@ -1,22 +1,72 @@
 //! macro tracker
 
 use std::{
+    collections::HashMap,
     error::Error,
-    io::{self, stdout, Write},
+    fmt::Display,
+    io::{self, stdin, stdout, Write},
     ops::{AddAssign, Mul},
     path::Path,
     str::FromStr,
+    time::{Duration, Instant},
 };
 
+use chrono::{Datelike, Days, Local, Months, NaiveDate, NaiveTime};
+use config::Config;
 use crossterm::{
-    cursor::{self, MoveDown, MoveLeft, MoveTo, MoveUp},
-    event::{read, Event, KeyCode},
-    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    cursor::{self, MoveTo},
+    event::{
+        poll, read, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
+    style::{Color, ResetColor, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
     ExecutableCommand, QueueableCommand,
 };
+use events::Events;
+use inbox::{Capture, Inbox};
+use input::Input;
+use keys::{Action, Binding};
+use layout::{fit, Border, Constraint, Direction, Rect};
+use log::{Entry, Log, Usage, DATE_FMT, NOTE_SEP, TIME_FMT};
+use parse::{parse_entries, resolve_named};
+use search::Query;
+use targets::{Goals, Targets};
+use task::Task;
+use term::RawMode;
+use unicode_width::UnicodeWidthStr;
 
-#[allow(unused)]
-#[derive(Debug)]
+mod config;
+mod custom;
+mod diff;
+mod events;
+#[cfg(feature = "arrow")]
+mod export;
+mod expr;
+mod inbox;
+mod input;
+mod keys;
+mod layout;
+mod log;
+mod model;
+mod parse;
+mod report;
+#[cfg(feature = "scale")]
+mod scale;
+mod search;
+mod tags;
+mod targets;
+mod task;
+mod term;
+mod theme;
+mod units;
+
+/// optional nutrients that may follow the required columns of the foods file,
+/// in this order. they are not tracked for every food, so a missing or empty
+/// column means the value is unknown rather than zero
+const NUTRIENTS: [&str; 3] = ["Fiber", "Sugar", "Sodium"];
+
+#[derive(Clone, Debug)]
 struct Food {
     name: String,
     calories: f64,
@@ -24,16 +74,88 @@ struct Food {
     fat: f64,
     protein: f64,
     unit: String,
+    /// the values of the optional [NUTRIENTS], if known
+    nutrients: [Option<f64>; NUTRIENTS.len()],
+    /// the brand, tags, and aliases of the food from the [tags] file, which
+    /// searches match as well as the name
+    keywords: Vec<String>,
+    /// a reminder shown whenever the food is logged, like `weigh dry`, from a
+    /// trailing note in the foods file
+    note: Option<String>,
+}
+
+impl Food {
+    /// the weight of one unit of the food in grams, if the unit is a weight
+    fn grams(&self) -> Option<f64> {
+        units::convert(1.0, &self.unit, "g")
+    }
+
+    /// a short summary of the macros in one unit of the food, followed by a
+    /// line of the optional nutrients if any are known
+    fn details(&self) -> Vec<String> {
+        let mut ret = vec![format!(
+            "{} kcal  P {}  C {}  F {} per {}",
+            self.calories, self.protein, self.carbs, self.fat, self.unit
+        )];
+        let nutrients: Vec<String> = NUTRIENTS
+            .iter()
+            .zip(self.nutrients)
+            .filter_map(|(name, v)| Some(format!("{name} {}", v?)))
+            .collect();
+        if !nutrients.is_empty() {
+            ret.push(nutrients.join("  "));
+        }
+        ret
+    }
+
+    /// the amount of `name` in one unit of the food, for use in formulas:
+    /// `calories`, `protein`, `carbs`, `fat`, or one of the optional nutrients
+    /// in lower case. unknown nutrients count as zero
+    fn variable(&self, name: &str) -> Option<f64> {
+        Some(match name {
+            "calories" => self.calories,
+            "protein" => self.protein,
+            "carbs" => self.carbs,
+            "fat" => self.fat,
+            _ => {
+                let i = NUTRIENTS
+                    .iter()
+                    .position(|n| n.eq_ignore_ascii_case(name))?;
+                self.nutrients[i].unwrap_or(0.0)
+            }
+        })
+    }
+
+    /// the food's nutrient density according to `formula`, or None if the
+    /// formula is invalid or undefined for the food, as for a food with no
+    /// calories
+    fn density(&self, formula: &str) -> Option<f64> {
+        expr::eval_with(formula, &|name| self.variable(name))
+            .ok()
+            .filter(|d| d.is_finite())
+    }
 }
 
 impl FromStr for Food {
     type Err = Box<dyn Error>;
 
+    /// parse a line of the foods tsv file, which may end in a note after a tab
+    /// and `# `, as in the log file
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, note) = match s.split_once(NOTE_SEP) {
+            Some((s, note)) => (s, Some(note.to_owned())),
+            None => (s, None),
+        };
         let fields: Vec<&str> = s.split('\t').collect();
-        if fields.len() != 6 {
+        if !(6..=6 + NUTRIENTS.len()).contains(&fields.len()) {
             Err("invalid field number")?;
         }
+        let mut nutrients = [None; NUTRIENTS.len()];
+        for (n, field) in nutrients.iter_mut().zip(&fields[6..]) {
+            if !field.is_empty() {
+                *n = Some(field.parse()?);
+            }
+        }
         Ok(Self {
             name: fields[0].to_owned(),
             calories: fields[1].parse()?,
@@ -41,26 +163,102 @@ impl FromStr for Food {
             fat: fields[3].parse()?,
             protein: fields[4].parse()?,
             unit: fields[5].to_owned(),
+            nutrients,
+            keywords: Vec::new(),
+            note,
         })
     }
 }
 
+impl Display for Food {
+    /// format `self` as a line of the foods tsv file, leaving out the note so
+    /// that the line can be embedded in a log entry
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.name,
+            self.calories,
+            self.carbs,
+            self.fat,
+            self.protein,
+            self.unit
+        )?;
+        // only write as many nutrient columns as needed to hold the last
+        // known value
+        let n = self
+            .nutrients
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |i| i + 1);
+        for v in &self.nutrients[..n] {
+            match v {
+                Some(v) => write!(f, "\t{v}")?,
+                None => write!(f, "\t")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 struct FoodQuantity(Food, f64);
 
-impl TryFrom<&[String; 7]> for FoodQuantity {
+impl TryFrom<&[Input; 7]> for FoodQuantity {
     type Error = Box<dyn Error>;
 
-    fn try_from(value: &[String; 7]) -> Result<Self, Self::Error> {
+    fn try_from(value: &[Input; 7]) -> Result<Self, Self::Error> {
         Ok(FoodQuantity(
             Food {
-                name: value[0].to_owned(),
-                calories: value[1].parse()?,
-                carbs: value[2].parse()?,
-                fat: value[3].parse()?,
-                protein: value[4].parse()?,
-                unit: value[5].to_owned(),
+                name: value[0].to_string(),
+                calories: expr::eval(&value[1])?,
+                carbs: expr::eval(&value[2])?,
+                fat: expr::eval(&value[3])?,
+                protein: expr::eval(&value[4])?,
+                unit: value[5].to_string(),
+                nutrients: [None; NUTRIENTS.len()],
+                keywords: Vec::new(),
+                note: None,
+            },
+            parse::quantity(&value[6], &value[5])?,
+        ))
+    }
+}
+
+/// a portion of a dish that is logged without being saved as a food. the macros
+/// are for the whole dish, and the portion is either a fraction like `1/3` or,
+/// if the total weight of the dish is given, the weight of the portion eaten
+struct DishPortion(Food, f64);
+
+impl TryFrom<&[Input; 7]> for DishPortion {
+    type Error = Box<dyn Error>;
+
+    fn try_from(value: &[Input; 7]) -> Result<Self, Self::Error> {
+        let portion = expr::eval(&value[6])?;
+        if portion <= 0.0 {
+            Err("portion must be more than 0")?;
+        }
+        let portion = if value[5].trim().is_empty() {
+            portion
+        } else {
+            let weight = expr::eval(&value[5])?;
+            if weight <= 0.0 {
+                Err("weight must be more than 0")?;
+            }
+            portion / weight
+        };
+        Ok(DishPortion(
+            Food {
+                name: value[0].to_string(),
+                calories: expr::eval(&value[1])?,
+                carbs: expr::eval(&value[2])?,
+                fat: expr::eval(&value[3])?,
+                protein: expr::eval(&value[4])?,
+                unit: "dish".to_owned(),
+                nutrients: [None; NUTRIENTS.len()],
+                keywords: Vec::new(),
+                note: None,
             },
-            value[6].parse()?,
+            portion,
         ))
     }
 }
@@ -74,6 +272,15 @@ impl AddAssign<Food> for Macros {
     }
 }
 
+impl AddAssign<Macros> for Macros {
+    fn add_assign(&mut self, rhs: Macros) {
+        self.calories += rhs.calories;
+        self.protein += rhs.protein;
+        self.carbs += rhs.carbs;
+        self.fat += rhs.fat;
+    }
+}
+
 impl Mul<f64> for Food {
     type Output = Food;
 
@@ -83,13 +290,18 @@ impl Mul<f64> for Food {
             carbs: self.carbs * rhs,
             fat: self.fat * rhs,
             protein: self.protein * rhs,
+            nutrients: self.nutrients.map(|n| n.map(|n| n * rhs)),
             ..self
         }
     }
 }
 
-fn load_foods(path: impl AsRef<Path>) -> Vec<Food> {
-    let s = std::fs::read_to_string(path).unwrap();
+fn load_foods(path: impl AsRef<Path>) -> io::Result<Vec<Food>> {
+    Ok(parse_foods(&std::fs::read_to_string(path)?))
+}
+
+/// parse the lines of a foods tsv file, skipping comments and invalid lines
+fn parse_foods(s: &str) -> Vec<Food> {
     let foods: Vec<Food> = s
         .lines()
         .filter_map(|line| {
@@ -114,12 +326,11 @@ fn load_foods(path: impl AsRef<Path>) -> Vec<Food> {
 //
 // Interface enhancements:
 // 1. Edit/Delete previous entries
-// 2. Navigate between dates
 //
 // Other enhancements:
 // 1. Use a real database, not a tsv file
 
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 struct Macros {
     calories: f64,
     carbs: f64,
@@ -128,9 +339,26 @@ struct Macros {
 }
 
 /// the current state of the program
+#[derive(Clone, Copy, PartialEq)]
 enum State {
     Main,
     AddFood,
+    CopyDay,
+    QuickAdd,
+    SplitDish,
+    Reports,
+    Report,
+    Prompt,
+    Inbox,
+    Review,
+    Calendar,
+    Weigh,
+    Numpad,
+    Compare,
+    History,
+    FoodCompare,
+    Search,
+    Help,
 }
 
 impl State {
@@ -141,6 +369,334 @@ impl State {
     fn is_add_food(&self) -> bool {
         matches!(self, Self::AddFood)
     }
+
+    /// Returns `true` if the state is [`CopyDay`].
+    ///
+    /// [`CopyDay`]: State::CopyDay
+    #[must_use]
+    fn is_copy_day(&self) -> bool {
+        matches!(self, Self::CopyDay)
+    }
+
+    /// Returns `true` if the state is [`QuickAdd`].
+    ///
+    /// [`QuickAdd`]: State::QuickAdd
+    #[must_use]
+    fn is_quick_add(&self) -> bool {
+        matches!(self, Self::QuickAdd)
+    }
+
+    /// Returns `true` if the state is [`SplitDish`].
+    ///
+    /// [`SplitDish`]: State::SplitDish
+    #[must_use]
+    fn is_split_dish(&self) -> bool {
+        matches!(self, Self::SplitDish)
+    }
+
+    /// Returns `true` if the state is [`Reports`].
+    ///
+    /// [`Reports`]: State::Reports
+    #[must_use]
+    fn is_reports(&self) -> bool {
+        matches!(self, Self::Reports)
+    }
+
+    /// Returns `true` if the state is [`Report`].
+    ///
+    /// [`Report`]: State::Report
+    #[must_use]
+    fn is_report(&self) -> bool {
+        matches!(self, Self::Report)
+    }
+
+    /// Returns `true` if the state is [`Prompt`].
+    ///
+    /// [`Prompt`]: State::Prompt
+    #[must_use]
+    fn is_prompt(&self) -> bool {
+        matches!(self, Self::Prompt)
+    }
+
+    /// Returns `true` if the state is [`Inbox`].
+    ///
+    /// [`Inbox`]: State::Inbox
+    #[must_use]
+    fn is_inbox(&self) -> bool {
+        matches!(self, Self::Inbox)
+    }
+
+    /// Returns `true` if the state is [`Review`].
+    ///
+    /// [`Review`]: State::Review
+    #[must_use]
+    fn is_review(&self) -> bool {
+        matches!(self, Self::Review)
+    }
+
+    /// Returns `true` if the state is [`Calendar`].
+    ///
+    /// [`Calendar`]: State::Calendar
+    #[must_use]
+    fn is_calendar(&self) -> bool {
+        matches!(self, Self::Calendar)
+    }
+
+    /// Returns `true` if the state is [`Weigh`].
+    ///
+    /// [`Weigh`]: State::Weigh
+    #[must_use]
+    fn is_weigh(&self) -> bool {
+        matches!(self, Self::Weigh)
+    }
+
+    /// Returns `true` if the state is [`Numpad`].
+    ///
+    /// [`Numpad`]: State::Numpad
+    #[must_use]
+    fn is_numpad(&self) -> bool {
+        matches!(self, Self::Numpad)
+    }
+
+    /// Returns `true` if the state is [`Compare`].
+    ///
+    /// [`Compare`]: State::Compare
+    #[must_use]
+    fn is_compare(&self) -> bool {
+        matches!(self, Self::Compare)
+    }
+
+    /// Returns `true` if the state is [`History`].
+    ///
+    /// [`History`]: State::History
+    #[must_use]
+    fn is_history(&self) -> bool {
+        matches!(self, Self::History)
+    }
+
+    /// Returns `true` if the state is [`FoodCompare`].
+    ///
+    /// [`FoodCompare`]: State::FoodCompare
+    #[must_use]
+    fn is_food_compare(&self) -> bool {
+        matches!(self, Self::FoodCompare)
+    }
+
+    /// Returns `true` if the state is [`Search`].
+    ///
+    /// [`Search`]: State::Search
+    #[must_use]
+    fn is_search(&self) -> bool {
+        matches!(self, Self::Search)
+    }
+
+    /// whether the screen for the state takes no typed text, so that vim keys
+    /// can stand in for its arrow keys
+    fn is_list(&self) -> bool {
+        matches!(
+            self,
+            Self::Reports
+                | Self::Report
+                | Self::Inbox
+                | Self::Review
+                | Self::Calendar
+                | Self::Compare
+                | Self::CopyDay
+        )
+    }
+
+    /// Returns `true` if the state is [`Main`].
+    ///
+    /// [`Main`]: State::Main
+    #[must_use]
+    fn is_main(&self) -> bool {
+        matches!(self, Self::Main)
+    }
+
+    /// Returns `true` if the state is [`Help`].
+    ///
+    /// [`Help`]: State::Help
+    #[must_use]
+    fn is_help(&self) -> bool {
+        matches!(self, Self::Help)
+    }
+}
+
+/// the panes of the main screen, which Tab moves the focus between
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Entries,
+    Totals,
+    Recent,
+}
+
+impl Pane {
+    /// the pane after this one in the focus cycle
+    fn next(self) -> Self {
+        match self {
+            Self::Entries => Self::Totals,
+            Self::Totals => Self::Recent,
+            Self::Recent => Self::Entries,
+        }
+    }
+}
+
+/// the state of the copy-from-date screen: the day being copied from, which of
+/// its entries are selected for copying, and the highlighted entry
+struct CopyDay {
+    from: NaiveDate,
+    selected: Vec<bool>,
+    cursor: usize,
+}
+
+/// the reports that can be opened from the Reports menu
+#[derive(Clone, Copy, Default)]
+enum ReportKind {
+    #[default]
+    Completeness,
+    TimeOfDay,
+    Weekdays,
+    Calories,
+    Monthly,
+    Trends,
+    Split,
+    TopFoods,
+    Yearly,
+    Day,
+    /// the custom report at this index in the config
+    Custom(usize),
+}
+
+impl ReportKind {
+    const ALL: [ReportKind; 10] = [
+        Self::Completeness,
+        Self::TimeOfDay,
+        Self::Weekdays,
+        Self::Calories,
+        Self::Monthly,
+        Self::Trends,
+        Self::Split,
+        Self::TopFoods,
+        Self::Yearly,
+        Self::Day,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ReportKind::Completeness => "Nutrient completeness",
+            ReportKind::TimeOfDay => "Intake by time of day",
+            ReportKind::Weekdays => "Weekdays and weekends",
+            ReportKind::Calories => "Daily calories and events",
+            ReportKind::Monthly => "Monthly summary",
+            ReportKind::Trends => "Trends",
+            ReportKind::Split => "Macro split",
+            ReportKind::TopFoods => "Top foods",
+            ReportKind::Yearly => "Year in review",
+            ReportKind::Day => "Day summary",
+            ReportKind::Custom(_) => "Custom report",
+        }
+    }
+
+    /// whether the report covers a period of days ending on the current day
+    fn uses_period(&self) -> bool {
+        !matches!(
+            self,
+            ReportKind::Completeness
+                | ReportKind::Monthly
+                | ReportKind::Yearly
+                | ReportKind::Day
+                | ReportKind::Custom(_)
+        )
+    }
+
+    /// whether the report covers a calendar month
+    fn by_month(&self) -> bool {
+        matches!(self, ReportKind::Monthly)
+    }
+
+    /// whether the report covers a calendar year
+    fn by_year(&self) -> bool {
+        matches!(self, ReportKind::Yearly)
+    }
+}
+
+/// the lengths in days of the periods a report can cover
+const PERIODS: [u64; 5] = [7, 14, 30, 90, 365];
+
+/// a read-only page of text shown by the Report screen
+#[derive(Default)]
+struct Report {
+    kind: ReportKind,
+    /// the index into [PERIODS] of the period covered by the report
+    period: usize,
+    /// the first day of the month covered by monthly reports
+    month: NaiveDate,
+    /// the year covered by yearly reports
+    year: i32,
+    title: String,
+    lines: Vec<String>,
+    /// the index of the first line shown
+    scroll: usize,
+}
+
+/// the number of days back the Review screen looks for entries to clean up
+const REVIEW_DAYS: u64 = 14;
+
+/// something listed on the Review screen as needing attention
+enum ReviewItem {
+    /// the index of a capture in the inbox
+    Capture(usize),
+    /// an entry on a given day, its index within that day, and why it is
+    /// listed
+    Entry(NaiveDate, usize, String),
+}
+
+/// what to do with the input of a [Prompt] once it is accepted
+enum PromptAction {
+    /// set the time of the selected entry
+    EntryTime,
+    /// set the note on the selected entry
+    EntryNote,
+    /// set the note on the current day
+    DayNote,
+    /// log the given quantity of a food
+    Quantity(Food),
+    /// record an event on the current day
+    Event,
+    /// set the percentage of the selected entry that was eaten
+    Consumed,
+    /// set or clear the goals for the current day alone
+    DayGoals,
+    /// run a command typed on the command line
+    Command,
+}
+
+/// a single line of input shown over the main screen
+struct Prompt {
+    label: String,
+    input: String,
+    action: PromptAction,
+    /// why the input was not accepted, if it was not
+    error: Option<String>,
+}
+
+/// a message shown in the bottom edge of the boundary until the next key or
+/// for [STATUS_TIME]
+struct Status {
+    text: String,
+    /// whether the message is an error, shown in red
+    error: bool,
+    shown: Instant,
+}
+
+impl Status {
+    fn new(text: impl Into<String>, error: bool) -> Self {
+        Self {
+            text: text.into(),
+            error,
+            shown: Instant::now(),
+        }
+    }
 }
 
 #[allow(unused)]
@@ -149,8 +705,94 @@ struct Tui<'a, W> {
     cols: u16,
     rows: u16,
     foods: Vec<Food>,
-    today: Macros,
-    buf: [String; 7],
+    /// [Tui::foods] prepared for searching
+    index: search::Index,
+    log: Log,
+    /// how often and how recently each food was logged, kept from
+    /// [Tui::log] to rank the foods searched for without rereading it
+    usage: HashMap<String, Usage>,
+    config: Config,
+    /// the characters boxes are drawn with
+    border: &'static Border,
+    targets: Targets,
+    /// the day currently being displayed and logged to
+    date: NaiveDate,
+    /// the index of the highlighted entry in the day's list
+    selected: usize,
+    /// the pane of the main screen that the arrow keys act on
+    pane: Pane,
+    /// the highlighted food in the Recent pane
+    recent_cursor: usize,
+    /// how many lines the Totals pane is scrolled down
+    totals_scroll: usize,
+    /// the fields of the AddFood form
+    buf: [Input; 7],
+    /// the index of the field in [Tui::buf] being edited
+    form_field: usize,
+    /// the fields to show errors for, once they have been filled in and left or
+    /// the form has been submitted. editing a field unchecks it
+    form_checked: [bool; 7],
+    /// the highlighted food in the list below the Food Name field, if any
+    candidate: Option<usize>,
+    /// entries queued in the AddFood form to be submitted together
+    batch: Vec<Entry>,
+    copy: CopyDay,
+    /// the free-form input line of the QuickAdd screen
+    quick: String,
+    inbox: Inbox,
+    events: Events,
+    /// the highlighted capture on the Inbox screen
+    inbox_cursor: usize,
+    /// the index of the capture being resolved on the QuickAdd screen, if any
+    resolving: Option<usize>,
+    review: Vec<ReviewItem>,
+    /// the highlighted item on the Review screen
+    review_cursor: usize,
+    /// the highlighted day on the Calendar screen
+    calendar: NaiveDate,
+    /// the quantity being typed for the selected entry on the Weigh screen
+    weigh: String,
+    /// the food number and quantity fields of the Numpad screen
+    numpad: [String; 2],
+    /// the active field on the Numpad screen
+    numpad_field: usize,
+    /// the day compared against the current day on the Compare screen
+    compare: NaiveDate,
+    /// the food name being searched for on the History screen
+    history: String,
+    /// the highlighted match on the History screen
+    history_cursor: usize,
+    /// the food names being searched for in the two panes of the FoodCompare
+    /// screen
+    food_compare: [String; 2],
+    /// the highlighted match in each pane of the FoodCompare screen
+    food_compare_cursor: [usize; 2],
+    /// the pane being typed in on the FoodCompare screen
+    food_compare_pane: usize,
+    /// how food names are matched on the screens that search them
+    search_mode: search::Mode,
+    /// the food name being searched for on the Search screen
+    search: String,
+    /// the highlighted match on the Search screen
+    search_cursor: usize,
+    /// the highlighted entry in the Reports menu
+    reports_cursor: usize,
+    report: Report,
+    prompt: Prompt,
+    /// the message in the status bar, if any
+    status: Option<Status>,
+    /// whether the user has asked to quit
+    quit: bool,
+    /// whether the day is being shown read-only by `macroni follow`, with no
+    /// entry selected
+    following: bool,
+    /// the screens that led to the current one, most recent last, for going
+    /// back to with Esc
+    screens: Vec<State>,
+    /// the screen the help was opened on
+    helped: State,
+    /// the weight being read from the scale in the background, if any
+    reading: Option<Task<f64>>,
     state: State,
 }
 
@@ -167,24 +809,129 @@ where
     }
 }
 
-const HELP_HEIGHT: u16 = 3;
+const HELP_HEIGHT: u16 = 5;
+/// the fraction of a goal past which a total is shown as nearing it
+const NEAR_GOAL: f64 = 0.9;
+/// the width of the progress bars of the totals against their goals
+const GAUGE_WIDTH: usize = 20;
+/// how long a message stays in the status bar
+const STATUS_TIME: Duration = Duration::from_secs(4);
 const HELP_PAD: u16 = 5;
 
+/// labels for the fields of the AddFood form
+const LABELS: [&str; 7] = [
+    "Food Name:",
+    " Calories:",
+    "    Carbs:",
+    "      Fat:",
+    "  Protein:",
+    "    Units:",
+    " Quantity:",
+];
+/// labels for the AddFood form when it is used to log a portion of a dish. the
+/// macros are the totals for the whole dish
+const DISH_LABELS: [&str; 7] = [
+    "Dish Name:",
+    " Calories:",
+    "    Carbs:",
+    "      Fat:",
+    "  Protein:",
+    "   Weight:",
+    "  Portion:",
+];
+/// the number of recent foods shown on the main screen
+const RECENT: usize = 9;
+
+/// the width of the longest entry in [LABELS]
+const MAX_WIDTH: u16 = 10;
+/// the width of the input boxes in the AddFood form when there is room
+const INPUT_WIDTH: u16 = 50;
+/// the number of matching foods listed below the Food Name field
+const CANDIDATES: usize = 5;
+
 impl<'a, W> Tui<'a, W>
 where
     W: QueueableCommand + Write,
 {
-    fn new(w: &'a mut W, foods: Vec<Food>) -> Self {
+    fn new(
+        w: &'a mut W,
+        foods: Vec<Food>,
+        log: Log,
+        config: Config,
+        targets: Targets,
+        inbox: Inbox,
+        events: Events,
+    ) -> Self {
         let (cols, rows) = terminal::size().unwrap();
         const S: String = String::new();
+        let date = Local::now().date_naive();
+        // the foods logged before this session can be recalled in the Food
+        // Name field like those entered during it
+        let mut buf: [Input; 7] = Default::default();
+        for food in log.recent_foods(RECENT).iter().rev() {
+            buf[0].push_history(&food.name);
+        }
         Self {
             w,
             cols,
             rows,
+            index: search::Index::new(&foods),
             foods,
-            today: Macros::default(),
+            usage: log.usage(),
+            log,
+            border: Border::new(config.ascii_borders),
+            config,
+            targets,
+            date,
+            selected: 0,
+            pane: Pane::Entries,
+            recent_cursor: 0,
+            totals_scroll: 0,
             state: State::Main,
-            buf: [S; 7], // this has to be the same as the fields in Food + 1
+            buf,
+            form_field: 0,
+            form_checked: [false; 7],
+            batch: Vec::new(),
+            copy: CopyDay {
+                from: date,
+                selected: Vec::new(),
+                cursor: 0,
+            },
+            quick: String::new(),
+            inbox,
+            events,
+            inbox_cursor: 0,
+            resolving: None,
+            review: Vec::new(),
+            review_cursor: 0,
+            calendar: date,
+            weigh: String::new(),
+            numpad: [S; 2],
+            numpad_field: 0,
+            compare: date,
+            history: String::new(),
+            history_cursor: 0,
+            food_compare: Default::default(),
+            food_compare_cursor: [0; 2],
+            food_compare_pane: 0,
+            search_mode: search::Mode::default(),
+            search: String::new(),
+            search_cursor: 0,
+            candidate: None,
+            reports_cursor: 0,
+            report: Report::default(),
+            status: None,
+            quit: false,
+            following: false,
+            screens: Vec::new(),
+            helped: State::Main,
+            reading: None,
+            prompt: Prompt {
+                label: String::new(),
+                input: String::new(),
+                action: PromptAction::EntryTime,
+                error: None,
+            },
         }
     }
 
@@ -211,252 +958,3995 @@ where
         Ok(())
     }
 
+    /// the whole screen above the help, which the boundary is drawn around
+    fn frame(&self) -> Rect {
+        Rect::new(0, 0, self.cols, self.rows - HELP_HEIGHT + 1)
+    }
+
+    /// the area inside the boundary that screens draw in, less a column of
+    /// padding on each side
+    fn body(&self) -> Rect {
+        self.frame().inner(2, 1)
+    }
+
+    /// the usual layout of a screen: a title row and, after a blank row, the
+    /// rest of the body
+    fn title_layout(&self) -> [Rect; 2] {
+        use Constraint::*;
+        let [title, _, rest] = self
+            .body()
+            .split(Direction::Vertical, [Length(1), Length(1), Fill]);
+        [title, rest]
+    }
+
     /// draw a bounding box around the whole window with unicode light box
-    /// drawing characters. TODO factor out the code to draw any rectangle
+    /// drawing characters, with the status message in its bottom edge
     fn draw_boundary(&mut self) -> io::Result<()> {
-        let (x1, y1) = (0, 0);
-        let (x2, y2) = (self.cols, self.rows - HELP_HEIGHT);
+        self.draw_block(self.frame())?;
+        if let Some(Status { text, error, .. }) = &self.status {
+            let text = format!(" {text} ");
+            let theme = self.config.theme;
+            let color = if *error { theme.error } else { theme.good };
+            let width = self.cols.saturating_sub(4) as usize;
+            self.move_to(2, self.frame().bottom())?;
+            self.write_colored(layout::truncate(&text, width), color)?;
+        }
+        self.flush()?;
+        Ok(())
+    }
 
-        self.draw_rect(x1, y1, x2, y2)?;
+    /// show `text` in the status bar, in red if it is an `error`
+    fn set_status(&mut self, text: impl Into<String>, error: bool) {
+        self.status = Some(Status::new(text, error));
+    }
 
-        self.flush()?;
+    /// how long the status message has left to be shown, if there is one
+    fn status_left(&self) -> Option<Duration> {
+        let shown = self.status.as_ref()?.shown;
+        Some(STATUS_TIME.saturating_sub(shown.elapsed()))
+    }
 
+    /// remove the status message, if any, redrawing the screen without it
+    fn clear_status(&mut self) -> io::Result<()> {
+        if self.status.take().is_some() {
+            self.render()?;
+        }
         Ok(())
     }
 
-    /// draw the rectangle from the upper left corner (x1, y1) to the bottom
-    /// right corner (x2, y2)
-    fn draw_rect(
-        &mut self,
-        x1: u16,
-        y1: u16,
-        x2: u16,
-        y2: u16,
-    ) -> Result<(), io::Error> {
-        for x in x1 + 1..x2 {
-            self.queue(MoveTo(x, y1))?.write_all("─".as_bytes())?;
-            self.queue(MoveTo(x, y2))?.write_all("─".as_bytes())?;
-        }
+    /// draw a box just inside the edges of `area`
+    fn draw_block(&mut self, area: Rect) -> io::Result<()> {
+        let (x1, y1, x2, y2) = (area.x, area.y, area.right(), area.bottom());
+        let b = self.border;
+        let line = b.line((x2 - x1).saturating_sub(1) as usize);
+        self.move_to(x1, y1)?;
+        self.write_str(&format!("{}{line}{}", b.top_left, b.top_right))?;
         for y in y1 + 1..y2 {
-            self.queue(MoveTo(x1, y))?.write_all("│".as_bytes())?;
-            self.w.queue(MoveTo(x2, y))?.write_all("│".as_bytes())?;
+            self.move_to(x1, y)?;
+            self.write_str(b.vertical.encode_utf8(&mut [0; 4]))?;
+            self.move_to(x2, y)?;
+            self.write_str(b.vertical.encode_utf8(&mut [0; 4]))?;
         }
-        self.queue(MoveTo(x1, y1))?.write_all("┌".as_bytes())?;
-        self.queue(MoveTo(x2, y1))?.write_all("┐".as_bytes())?;
-        self.queue(MoveTo(x1, y2))?.write_all("└".as_bytes())?;
-        self.queue(MoveTo(x2, y2))?.write_all("┘".as_bytes())?;
+        self.move_to(x1, y2)?;
+        self.write_str(&format!("{}{line}{}", b.bottom_left, b.bottom_right))?;
         Ok(())
     }
 
-    /// draw the help menu at the bottom of the screen
-    fn draw_help(&mut self, labels: &[&str]) -> io::Result<()> {
-        let mut n = 0;
-        for (i, label) in labels.iter().enumerate() {
-            self.move_to(
-                1 + n as u16 + i as u16 * HELP_PAD,
-                self.rows - HELP_HEIGHT + 1,
-            )?;
-            n += self.write_str(label)?;
+    /// draw `title` at the top of `area` in the accent color
+    fn draw_title(&mut self, area: Rect, title: &str) -> io::Result<()> {
+        self.move_to(area.x, area.y)?;
+        let title = layout::truncate(title, area.width as usize);
+        self.write_colored(title, self.config.theme.accent)
+    }
+
+    /// draw `lines` down `area`, cutting them off at its edges
+    fn draw_lines(&mut self, area: Rect, lines: &[String]) -> io::Result<()> {
+        let width = area.width as usize;
+        for (i, line) in lines.iter().take(area.height as usize).enumerate() {
+            self.move_to(area.x, area.y + i as u16)?;
+            self.write_str(layout::truncate(line, width))?;
         }
-        self.flush()?;
         Ok(())
     }
 
-    fn draw_today(&mut self) -> io::Result<()> {
-        let (x, y) = self.center();
-        let s = format!(
-            "Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
-            self.today.calories,
-            self.today.protein,
-            self.today.carbs,
-            self.today.fat
+    /// draw `items` down `area` with an arrow marking the `selected` one, if
+    /// any, scrolling so that it stays in view. when they do not all fit, the
+    /// last row shows which of them are in view
+    fn draw_list(
+        &mut self,
+        area: Rect,
+        items: &[String],
+        selected: Option<usize>,
+    ) -> io::Result<()> {
+        let n = items.len();
+        let scrolls = n > area.height as usize && area.height > 1;
+        let [area, position] = area.split(
+            Direction::Vertical,
+            [Constraint::Fill, Constraint::Length(scrolls.into())],
         );
-        let x = x - s.len() as u16 / 2;
-        self.queue(MoveTo(x, y))?;
-        self.write_str("Today:")?;
-        self.move_to(x, y + 1)?;
-        self.write_str(&s)?;
-        self.flush()?;
+        let start = selected.map_or(0, |selected| {
+            (selected + 1).saturating_sub(area.height as usize)
+        });
+        let lines: Vec<String> = items
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(i, item)| {
+                let arrow = if Some(i) == selected { '>' } else { ' ' };
+                format!("{arrow} {item}")
+            })
+            .collect();
+        self.draw_lines(area, &lines)?;
+        if scrolls {
+            let end = (start + area.height as usize).min(n);
+            let line = format!("  [{}-{end} of {n}]", start + 1);
+            self.draw_lines(position, &[line])?;
+        }
         Ok(())
     }
 
-    fn render_main(&mut self) -> io::Result<()> {
-        self.state = State::Main;
-        self.execute(cursor::Hide)?;
-        self.execute(Clear(ClearType::All))?;
-        self.draw_boundary()?;
-        self.draw_help(&["q Quit", "a Add Food"])?;
-        self.draw_today()
+    /// draw the help menu at the bottom of the screen, wrapping onto the
+    /// following lines when the labels do not fit on one
+    fn draw_help(&mut self, labels: &[&str]) -> io::Result<()> {
+        // clear any help already drawn, as by draw_main under a prompt
+        for y in self.rows - HELP_HEIGHT + 1..self.rows {
+            self.move_to(0, y)?;
+            self.queue(Clear(ClearType::CurrentLine))?;
+        }
+        let mut x = 1;
+        let mut y = self.rows - HELP_HEIGHT + 1;
+        for label in labels {
+            let len = label.chars().count() as u16;
+            if x > 1 && x + len >= self.cols {
+                x = 1;
+                y += 1;
+            }
+            if y >= self.rows {
+                break;
+            }
+            self.move_to(x, y)?;
+            // the key in the accent color and what it does after it
+            let (key, action) = label.split_once(' ').unwrap_or((label, ""));
+            self.write_colored(key, self.config.theme.accent)?;
+            self.write_str(&format!(" {action}"))?;
+            x += len + HELP_PAD;
+        }
+        self.flush()?;
+        Ok(())
     }
 
-    fn add_food(&mut self) -> io::Result<()> {
-        self.execute(Clear(ClearType::All))?;
-        self.draw_boundary()?;
-        self.draw_help(&[
-            "Tab Next",
-            "S-Tab Prev",
-            "Ret Submit",
-            "Esc Cancel",
-        ])?;
-        self.state = State::AddFood;
+    /// the heading of the main screen: the day, its note, whether it has goals
+    /// of its own, and its events
+    fn day_label(&self) -> String {
+        let label = if self.date == Local::now().date_naive() {
+            "Today:".to_owned()
+        } else {
+            format!(
+                "{} {}:",
+                self.date.format("%a"),
+                self.date.format(DATE_FMT)
+            )
+        };
+        let mut label = match self.log.day_note(self.date) {
+            Some(note) => format!("{label} {note}"),
+            None => label,
+        };
+        if self.targets.get_override(self.date).is_some() {
+            label.push_str(" (own goals)");
+        }
+        for event in self.events.day(self.date) {
+            label.push_str(&format!(" ◆ {event}"));
+        }
+        label
+    }
 
-        // the idea here is to replicate an HTML form essentially:
-        //
-        // Food Name: [___________________]
-        //  Calories: [___________________]
-        //
-        // and so on, with Tab moving between the fields. We'll also need to
-        // show the cursor again here. Basics are actually easy, showing the
-        // completion candidates will be most of the work.
+    /// the lines of the Totals pane, each with its color if it shows a goal
+    /// that is nearly met or has been exceeded
+    fn totals_lines(&self) -> Vec<(String, Option<Color>)> {
+        let today = self.log.totals(self.date);
+        let goals = *self.targets.get(self.date);
+        let mut lines: Vec<(String, Option<Color>)> = Vec::new();
+        let theme = self.config.theme;
+        // with goals, each macro gets its own line with the names aligned
+        let per_macro = goals.fields().iter().any(Option::is_some);
+        if self.config.show_macros && per_macro {
+            let eaten = [today.calories, today.protein, today.carbs, today.fat];
+            let names = ["Calories", "Protein", "Carbs", "Fat"];
+            for ((name, eaten), goal) in
+                names.iter().zip(eaten).zip(goals.fields())
+            {
+                let Some(goal) = goal else {
+                    lines.push((format!("{name:>8}: {eaten:.0}"), None));
+                    continue;
+                };
+                let left = if eaten > goal {
+                    format!("{:.0} over", eaten - goal)
+                } else {
+                    format!("{:.0} left", goal - eaten)
+                };
+                let color = if eaten > goal {
+                    Some(theme.over)
+                } else if eaten >= NEAR_GOAL * goal {
+                    Some(theme.near)
+                } else {
+                    None
+                };
+                lines.push((
+                    format!("{name:>8}: {eaten:.0} / {goal:.0} ({left})"),
+                    color,
+                ));
+                if goal > 0.0 {
+                    let gauge = self.border.gauge(eaten / goal, GAUGE_WIDTH);
+                    let percent = 100.0 * eaten / goal;
+                    lines.push((
+                        format!("{:8}  {gauge} {percent:.0}%", ""),
+                        color.or(Some(theme.good)),
+                    ));
+                }
+            }
+        } else if self.config.show_macros {
+            lines.push((
+                format!(
+                    "Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+                    today.calories, today.protein, today.carbs, today.fat
+                ),
+                None,
+            ));
+        }
+        if self.config.show_macros && self.config.deltas {
+            lines.extend(self.deltas(&today).into_iter().map(|l| (l, None)));
+        }
+        if self.config.show_macros {
+            if let Some(split) = report::macro_split(&today) {
+                let label = if per_macro { "   Split:" } else { "Split:" };
+                let line = format!("{label} {}", report::split_line(split));
+                lines.push((line, None));
+            }
+        }
+        let mut alt = Vec::new();
+        if self.config.exchanges {
+            alt.push(format!("Carb Exchanges: {:.1}", today.carbs / 15.0));
+        }
+        if let Some(formula) = &self.config.points {
+            let points: Result<f64, _> = self
+                .log
+                .day(self.date)
+                .iter()
+                .map(|e| expr::eval_with(formula, &|name| e.variable(name)))
+                .sum();
+            match points {
+                Ok(p) => alt.push(format!("Points: {p:.0}")),
+                Err(_) => alt.push("Points: invalid formula".to_owned()),
+            }
+        }
+        if !alt.is_empty() {
+            lines.push((alt.join(" "), None));
+        }
+        lines
+    }
 
-        const LABELS: [&str; 7] = [
-            "Food Name:",
-            " Calories:",
-            "  Protein:",
-            "    Carbs:",
-            "      Fat:",
-            "    Units:",
-            " Quantity:",
-        ];
-        const MAX_WIDTH: u16 = 10;
-        const INPUT_WIDTH: u16 = 50;
+    /// the parts of the main screen: the day's heading, the Entries and Totals
+    /// panes side by side, and the Recent pane along the bottom
+    fn main_layout(&self) -> [Rect; 4] {
+        use Constraint::*;
+        let [title, rest] = self.title_layout();
+        let recent = self.recent_lines(rest.width.saturating_sub(4)).len();
+        let recent = (recent.max(2) as u16 + 2).min(rest.height / 3);
+        let [top, recent] =
+            rest.split(Direction::Vertical, [Fill, Length(recent)]);
+        let lines = self.totals_lines();
+        let width = lines.iter().map(|(l, _)| l.width()).max().unwrap_or(0);
+        let width = (width as u16 + 4).max(20).min(top.width / 2);
+        let [entries, totals] =
+            top.split(Direction::Horizontal, [Fill, Length(width)]);
+        [title, entries, totals, recent]
+    }
 
-        // so we want to center 10 + 50 + 1 characters in the width of the
-        // screen, and there are going to be 6 lines: 5 labels + accept
+    /// draw a box around `area` titled `title`, in the accent color when
+    /// `pane` has the focus
+    fn draw_pane(
+        &mut self,
+        area: Rect,
+        title: &str,
+        pane: Pane,
+    ) -> io::Result<()> {
+        if self.pane == pane {
+            self.queue(SetForegroundColor(self.config.theme.accent))?;
+        }
+        self.draw_block(area)?;
+        self.queue(ResetColor)?;
+        self.draw_title(area.inner(2, 0), title)
+    }
 
-        let x = self.cols / 2 - (MAX_WIDTH + INPUT_WIDTH + 1) / 2;
-        let y = self.rows / 2 - (3 * LABELS.len() + 1) as u16 / 2;
+    /// draw the day's heading and the panes of the main screen
+    fn draw_today(&mut self) -> io::Result<()> {
+        let [title, entries, totals, recent] = self.main_layout();
+        let label = self.day_label();
+        self.draw_title(title, &label)?;
+        self.draw_pane(entries, " Entries ", Pane::Entries)?;
+        self.draw_entries(entries.inner(2, 1))?;
+        self.draw_pane(totals, " Totals ", Pane::Totals)?;
+        self.draw_totals(totals.inner(2, 1))?;
+        self.draw_pane(recent, " Recent ", Pane::Recent)?;
+        let area = recent.inner(2, 1);
+        let lines = self.recent_lines(area.width);
+        self.draw_lines(area, &lines)?;
+        self.flush()
+    }
 
-        for (i, label) in LABELS.iter().enumerate() {
-            let i = 3 * i as u16;
-            self.move_to(x, y + i)?;
-            self.write_str(label)?;
-            self.draw_rect(
-                x + MAX_WIDTH + 1,
-                y + i - 1,
-                x + MAX_WIDTH + 1 + INPUT_WIDTH,
-                y + i + 1,
-            )?;
+    /// draw the day's totals down `area`, scrolled by [Tui::totals_scroll]
+    fn draw_totals(&mut self, area: Rect) -> io::Result<()> {
+        let lines = self.totals_lines();
+        let height = area.height as usize;
+        let start = self.totals_scroll.min(lines.len().saturating_sub(height));
+        for (i, (line, color)) in
+            lines.iter().skip(start).take(height).enumerate()
+        {
+            self.move_to(area.x, area.y + i as u16)?;
+            let line = layout::truncate(line, area.width as usize);
+            match color {
+                Some(color) => self.write_colored(line, *color)?,
+                None => {
+                    self.write_str(line)?;
+                }
+            }
         }
+        Ok(())
+    }
 
-        // move the cursor into the first box and show it
-        self.move_to(x + MAX_WIDTH + 2, y)?;
-        self.queue(cursor::Show)?;
+    /// lines comparing `today`'s totals with those of the day before and with
+    /// the average over the week before, leaving out either if nothing was
+    /// logged then
+    fn deltas(&self, today: &Macros) -> Vec<String> {
+        let Some(yesterday) = self.date.pred_opt() else {
+            return Vec::new();
+        };
+        let week = self.date - Days::new(7);
+        let others = [
+            ("Yesterday:", self.log.average(yesterday, yesterday)),
+            ("7-day avg:", self.log.average(week, yesterday)),
+        ];
+        let mut ret = Vec::new();
+        for (label, other) in others {
+            let Some(o) = other else {
+                continue;
+            };
+            ret.push(format!(
+                "{label} {:+.0} kcal  P {:+.0}  C {:+.0}  F {:+.0}",
+                today.calories - o.calories,
+                today.protein - o.protein,
+                today.carbs - o.carbs,
+                today.fat - o.fat,
+            ));
+        }
+        ret
+    }
 
-        // let (cols, rows) = terminal::size()?;
-        // // this is so stupid, just to avoid the double borrow
-        // let foods = std::mem::take(&mut self.foods);
-        // for (i, food) in foods.iter().enumerate() {
-        //     self.queue(cursor::MoveTo(cols / 2, rows / 2 + i as u16))?;
-        //     self.write_all(food.name.as_bytes())?;
-        // }
-        // self.foods = foods;
+    /// draw the day's entries in time order down `area`, marking the selected
+    /// one
+    fn draw_entries(&mut self, area: Rect) -> io::Result<()> {
+        let items: Vec<String> = self
+            .log
+            .day(self.date)
+            .iter()
+            .map(|entry| {
+                let time = match entry.time {
+                    Some(t) => t.format(TIME_FMT).to_string(),
+                    None => "     ".to_owned(),
+                };
+                let note = match &entry.note {
+                    Some(note) => format!(" # {note}"),
+                    None => String::new(),
+                };
+                format!("{time} {entry}{note}")
+            })
+            .collect();
+        // nothing is selected in follow mode
+        let selected = (!self.following).then_some(self.selected);
+        self.draw_list(area, &items, selected)
+    }
 
-        self.flush()?;
-        Ok(())
+    /// the nutrient density of `food` by the configured formula, if any
+    fn density(&self, food: &Food) -> Option<f64> {
+        food.density(self.config.density.as_ref()?)
     }
 
-    fn food_form(
-        &mut self,
-        event: crossterm::event::KeyEvent,
-        right: &mut u16,
-        field: &mut u16,
-    ) -> Result<(), io::Error> {
-        match event.code {
-            KeyCode::Char(c) => {
-                self.write_all(&[c as u8])?;
-                self.buf[*field as usize].push(c);
-                *right += 1;
-                self.flush()?;
+    /// the most recently logged foods, numbered for logging them again with
+    /// the number keys and run together into lines of at most `width`. the
+    /// highlighted one is marked while the Recent pane has the focus
+    fn recent_lines(&self, width: u16) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for (i, food) in self.log.recent_foods(RECENT).iter().enumerate() {
+            let focused = self.pane == Pane::Recent && i == self.recent_cursor;
+            let arrow = if focused { '>' } else { ' ' };
+            let item = match self.density(food) {
+                Some(d) => format!("{arrow} {} [{d:.1}] {}", i + 1, food.name),
+                None => format!("{arrow} {} {}", i + 1, food.name),
+            };
+            if !line.is_empty()
+                && line.width() + 2 + item.width() > width as usize
+            {
+                lines.push(std::mem::take(&mut line));
             }
-            KeyCode::Backspace => {
-                self.write_all(&[0x08, 0x20, 0x08])?;
-                self.buf[*field as usize].pop();
-                *right -= 1;
-                self.flush()?;
+            if !line.is_empty() {
+                line.push_str("  ");
             }
-            KeyCode::Tab => {
-                if *field < self.buf.len() as u16 - 1 {
-                    *field += 1;
-                    self.execute(MoveDown(3))?;
-                    if *right != 0 {
-                        // 0 defaults to 1...
-                        self.execute(MoveLeft(*right))?;
-                    }
-                    // zero actually isn't right here or in backtab. I need to
-                    // maintain the length of each field
-                    *right = 0;
-                }
-            }
-            KeyCode::BackTab => {
-                if *field > 0 {
-                    *field -= 1;
-                    self.execute(MoveUp(3))?;
-                    if *right != 0 {
-                        // 0 defaults to 1...
-                        self.execute(MoveLeft(*right))?;
-                    }
-                    *right = 0;
-                }
+            line.push_str(&item);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// draw the main screen without changing the state
+    fn draw_main(&mut self) -> io::Result<()> {
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Main)?;
+        self.draw_today()
+    }
+
+    /// the keys of the screen for `state`, leaving out those that do nothing
+    /// there at the moment, like Tab without a scale to read
+    fn bindings(&self, state: State) -> Vec<&'static Binding> {
+        let table = match state {
+            State::Main => return keys::main(self.config.vim),
+            State::AddFood | State::SplitDish => keys::FORM,
+            State::CopyDay => keys::COPY_DAY,
+            State::QuickAdd => keys::QUICK_ADD,
+            State::Reports => keys::REPORTS,
+            State::Report => keys::REPORT,
+            State::Prompt => keys::PROMPT,
+            State::Inbox => keys::INBOX,
+            State::Review => keys::REVIEW,
+            State::Calendar => keys::CALENDAR,
+            State::Weigh => keys::WEIGH,
+            State::Numpad => keys::NUMPAD,
+            State::Compare => keys::COMPARE,
+            State::History => keys::HISTORY,
+            State::FoodCompare => keys::FOOD_COMPARE,
+            State::Search => keys::SEARCH,
+            State::Help => keys::HELP_OVERLAY,
+        };
+        let kind = self.report.kind;
+        table
+            .iter()
+            .filter(|binding| match binding.action {
+                Action::Scale if state.is_prompt() => self.prompt_uses_scale(),
+                Action::Scale => self.config.scale.is_some(),
+                Action::Period => kind.uses_period(),
+                Action::Month if state.is_report() => kind.by_month(),
+                Action::Year => kind.by_year(),
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// the action of the key pressed in `event` on the current screen, if any
+    fn action(&self, event: KeyEvent) -> Option<Action> {
+        keys::find(&self.bindings(self.state), event)
+    }
+
+    /// `binding` as it is shown in the help bar, with the current search mode
+    /// after the key switching it
+    fn label(&self, binding: &Binding) -> String {
+        match binding.action {
+            Action::SearchMode => format!(
+                "{} {}: {}",
+                binding.keys,
+                binding.desc,
+                self.search_mode.name()
+            ),
+            _ => format!("{} {}", binding.keys, binding.desc),
+        }
+    }
+
+    /// draw the help bar with the keys of the screen for `state`
+    fn draw_keys(&mut self, state: State) -> io::Result<()> {
+        let labels: Vec<String> = self
+            .bindings(state)
+            .iter()
+            .map(|binding| self.label(binding))
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        self.draw_help(&labels)
+    }
+
+    /// list every key of the current screen in a box over it
+    fn help(&mut self) -> io::Result<()> {
+        self.helped = self.state;
+        self.render_help()
+    }
+
+    /// return from the help to the screen it was opened on
+    fn close_help(&mut self) -> io::Result<()> {
+        self.state = self.helped;
+        self.render()
+    }
+
+    fn help_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        match self.action(event) {
+            Some(Action::Back) => self.close_help(),
+            _ => Ok(()),
+        }
+    }
+
+    /// draw the screen the help was opened on with its keys in a box over it
+    fn render_help(&mut self) -> io::Result<()> {
+        self.state = self.helped;
+        self.render()?;
+        self.state = State::Help;
+        self.execute(cursor::Hide)?;
+        let bindings = self.bindings(self.helped);
+        let keys_width = bindings.iter().map(|b| b.keys.width()).max();
+        let desc_width = bindings.iter().map(|b| b.desc.width()).max();
+        let (keys_width, desc_width) =
+            (keys_width.unwrap_or(0), desc_width.unwrap_or(0));
+        // as many columns as it takes to fit the keys in the body
+        let rows = self.body().height.saturating_sub(2).max(1) as usize;
+        let columns = bindings.len().div_ceil(rows);
+        let rows = bindings.len().div_ceil(columns);
+        let column_width = keys_width + 1 + desc_width + 3;
+        let area = self
+            .body()
+            .centered((columns * column_width + 1) as u16, rows as u16 + 2);
+        let blank = " ".repeat(area.width as usize);
+        for y in area.y..=area.bottom() {
+            self.move_to(area.x, y)?;
+            self.write_str(&blank)?;
+        }
+        self.draw_block(area)?;
+        self.draw_title(area.inner(2, 0), " Keys ")?;
+        let inner = area.inner(2, 1);
+        for (i, binding) in bindings.iter().enumerate() {
+            let x = inner.x + ((i / rows) * column_width) as u16;
+            self.move_to(x, inner.y + (i % rows) as u16)?;
+            let keys = format!("{:>keys_width$}", binding.keys);
+            self.write_colored(&keys, self.config.theme.accent)?;
+            self.write_str(&format!(" {}", binding.desc))?;
+        }
+        self.draw_keys(State::Help)?;
+        self.flush()
+    }
+
+    /// do what `action` does on the main screen, where it was bound to `code`
+    fn act(&mut self, action: Action, code: KeyCode) -> io::Result<()> {
+        match action {
+            Action::Help => self.help(),
+            Action::Quit => {
+                self.quit = true;
+                Ok(())
+            }
+            Action::AddFood => self.add_food(),
+            Action::Search => self.search(),
+            Action::QuickAdd => self.quick_add(),
+            Action::Numpad => self.numpad(),
+            Action::SplitDish => self.split_dish(),
+            Action::CopyDay => self.copy_day(),
+            Action::Calendar => self.calendar(),
+            Action::Compare => self.compare(),
+            Action::Day => {
+                let back = matches!(code, KeyCode::Left | KeyCode::Char('h'));
+                self.shift_date(if back { -1 } else { 1 });
+                self.selected = 0;
+                self.render_main()
+            }
+            Action::Today => {
+                // return to today after jumping to another day
+                self.date = Local::now().date_naive();
+                self.render_main()
+            }
+            Action::Reports => self.render_reports(),
+            Action::Time => self.edit_time(),
+            Action::Quantity => {
+                let steps = if code == KeyCode::Char('-') {
+                    -1.0
+                } else {
+                    1.0
+                };
+                self.bump_quantity(steps)
+            }
+            Action::Weigh => self.weigh(),
+            Action::Repeat => self.repeat_entry(),
+            Action::Undo => self.undo(),
+            Action::Estimate => self.toggle_estimated(),
+            Action::Partial => self.edit_consumed(),
+            Action::Note => self.edit_entry_note(),
+            Action::DayNote => self.edit_day_note(),
+            Action::DayGoals => self.edit_day_goals(),
+            Action::Event => self.add_event(),
+            Action::Inbox => self.render_inbox(),
+            Action::Review => self.review(),
+            Action::History => self.history(),
+            Action::CompareFoods => self.food_compare(),
+            Action::Recent => match code {
+                KeyCode::Char(c @ '1'..='9') => {
+                    self.log_recent(c as usize - '1' as usize)
+                }
+                _ => Ok(()),
+            },
+            Action::Select => {
+                let up = matches!(code, KeyCode::Up | KeyCode::Char('k'));
+                self.select(if up { -1 } else { 1 })
+            }
+            Action::Pane => {
+                self.pane = self.pane.next();
+                self.render_main()
+            }
+            Action::LogRecent if self.pane == Pane::Recent => {
+                self.log_recent(self.recent_cursor)
+            }
+            Action::LogRecent => Ok(()),
+            Action::Command => {
+                self.prompt(":", String::new(), PromptAction::Command)
+            }
+            // the actions of the other screens
+            Action::Back
+            | Action::Accept
+            | Action::Next
+            | Action::Prev
+            | Action::SearchMode
+            | Action::Queue
+            | Action::Submit
+            | Action::Scale
+            | Action::Delete
+            | Action::Toggle
+            | Action::Month
+            | Action::Period
+            | Action::Year => Ok(()),
+        }
+    }
+
+    /// run a command typed on the command line: `goto DATE`, `export RANGE
+    /// [csv|md]`, `q`, or the name of anything in the main screen's help,
+    /// like `history`
+    fn run_command(&mut self, command: &str) -> io::Result<()> {
+        let (name, arg) = command
+            .split_once(' ')
+            .map_or((command, ""), |(name, arg)| (name, arg.trim()));
+        let error = match name {
+            "goto" => match NaiveDate::parse_from_str(arg, DATE_FMT) {
+                Ok(date) => {
+                    self.date = date;
+                    self.selected = 0;
+                    return self.render_main();
+                }
+                Err(_) => {
+                    format!("expected a date like 2024-05-01, got `{arg}`")
+                }
+            },
+            "export" => match self.export(arg) {
+                Ok(done) => {
+                    self.set_status(done, false);
+                    return self.render_main();
+                }
+                Err(e) => e,
+            },
+            "q" | "quit" => return self.act(Action::Quit, KeyCode::Null),
+            _ => match keys::MAIN.iter().find(|binding| {
+                binding.codes.len() == 1
+                    && binding.desc.eq_ignore_ascii_case(command)
+            }) {
+                Some(binding) => {
+                    return self.act(binding.action, binding.codes[0])
+                }
+                None => format!("unknown command `{command}`"),
+            },
+        };
+        self.prompt.error = Some(error);
+        self.render_prompt()
+    }
+
+    /// write the entries in `range`, a number of days or `day`, `week`,
+    /// `month`, `year`, or `all`, ending on the current day to a csv file, or
+    /// Markdown if it is followed by `md`. returns what was written
+    fn export(&self, arg: &str) -> Result<String, String> {
+        let (range, format) = arg.split_once(' ').unwrap_or((arg, "csv"));
+        let range = match range {
+            "day" => custom::Range::Days(1),
+            "week" => custom::Range::Days(7),
+            "month" => custom::Range::Month,
+            "year" => custom::Range::Year,
+            "all" => custom::Range::All,
+            n => custom::Range::Days(
+                n.parse().map_err(|_| format!("unknown range `{n}`"))?,
+            ),
+        };
+        let (from, to) = range.dates(&self.log, self.date);
+        let lines = match format.trim() {
+            "csv" => report::csv(&model::days(&self.log, from, to)),
+            "md" => {
+                report::markdown(&model::days(&self.log, from, to), from, to)
+            }
+            f => return Err(format!("unknown format `{f}`")),
+        };
+        let path = format!(
+            "macroni-{}-{}.{}",
+            from.format(DATE_FMT),
+            to.format(DATE_FMT),
+            format.trim()
+        );
+        let mut contents = lines.join("\n");
+        contents.push('\n');
+        std::fs::write(&path, contents).map_err(|e| e.to_string())?;
+        Ok(format!("Exported {from} to {to} to {path}"))
+    }
+
+    fn render_main(&mut self) -> io::Result<()> {
+        self.set_state(State::Main);
+        let n = self.log.day(self.date).len();
+        self.selected = self.selected.min(n.saturating_sub(1));
+        self.draw_main()
+    }
+
+    /// move the selection in the focused pane by `delta`: the highlighted
+    /// entry or recent food, or how far the totals are scrolled
+    fn select(&mut self, delta: isize) -> io::Result<()> {
+        match self.pane {
+            Pane::Entries => {
+                let n = self.log.day(self.date).len();
+                self.selected = self
+                    .selected
+                    .saturating_add_signed(delta)
+                    .min(n.saturating_sub(1));
+            }
+            Pane::Totals => {
+                let height = self.main_layout()[2].inner(2, 1).height;
+                let n = self.totals_lines().len();
+                self.totals_scroll = self
+                    .totals_scroll
+                    .saturating_add_signed(delta)
+                    .min(n.saturating_sub(height as usize));
+            }
+            Pane::Recent => {
+                let n = self.log.recent_foods(RECENT).len();
+                self.recent_cursor = self
+                    .recent_cursor
+                    .saturating_add_signed(delta)
+                    .min(n.saturating_sub(1));
+            }
+        }
+        self.render_main()
+    }
+
+    /// move the day being shown and logged to by `days`, which may be negative
+    fn shift_date(&mut self, days: i64) {
+        let delta = Days::new(days.unsigned_abs());
+        let date = if days < 0 {
+            self.date.checked_sub_days(delta)
+        } else {
+            self.date.checked_add_days(delta)
+        };
+        if let Some(date) = date {
+            self.date = date;
+        }
+    }
+
+    /// change the quantity of the selected entry by `steps` times the
+    /// configured step, without letting it drop to zero or below
+    fn bump_quantity(&mut self, steps: f64) -> io::Result<()> {
+        let delta = steps * self.config.step;
+        let Some(entry) = self.log.get_mut(self.date, self.selected) else {
+            return Ok(());
+        };
+        if entry.quantity + delta > 0.0 {
+            entry.quantity += delta;
+            self.save_log()?;
+            self.render_main()?;
+        }
+        Ok(())
+    }
+
+    /// toggle whether the selected entry is marked as an estimate
+    fn toggle_estimated(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.get_mut(self.date, self.selected) else {
+            return Ok(());
+        };
+        entry.estimated = !entry.estimated;
+        self.save_log()?;
+        self.render_main()
+    }
+
+    /// undo the last change saved to the log
+    fn undo(&mut self) -> io::Result<()> {
+        if self.log.undo()? {
+            self.render_main()?;
+        }
+        Ok(())
+    }
+
+    /// log the selected entry again at the current time
+    fn repeat_entry(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(self.selected) else {
+            return Ok(());
+        };
+        let mut entry = entry.clone();
+        entry.time = None;
+        self.add_entries(vec![entry]);
+        self.save_log()?;
+        self.render_main()
+    }
+
+    /// add `entries` to the current day as with [Tui::add_entry], reporting
+    /// what was added in the status bar
+    fn add_entries(&mut self, entries: Vec<Entry>) {
+        let calories: f64 = entries.iter().map(|e| e.macros().calories).sum();
+        let text = match entries.as_slice() {
+            [] => return,
+            [entry] => format!("Added {entry}"),
+            _ => {
+                format!("Added {} entries ({calories:.0} kcal)", entries.len())
+            }
+        };
+        for entry in entries {
+            self.add_entry(entry);
+        }
+        self.set_status(text, false);
+    }
+
+    /// add `entry` to the current day, stamping it with the current time if it
+    /// does not already have one
+    fn add_entry(&mut self, mut entry: Entry) {
+        // the current time means nothing for an entry logged to another day
+        if self.date == Local::now().date_naive() {
+            entry.time.get_or_insert_with(|| Local::now().time());
+        }
+        self.selected = self.log.add(self.date, entry);
+    }
+
+    /// open a prompt over the main screen
+    fn prompt(
+        &mut self,
+        label: impl Into<String>,
+        input: String,
+        action: PromptAction,
+    ) -> io::Result<()> {
+        self.set_state(State::Prompt);
+        self.prompt = Prompt {
+            label: label.into(),
+            input,
+            action,
+            error: None,
+        };
+        self.render_prompt()
+    }
+
+    /// start reading the weight on the configured scale, if there is one,
+    /// for the prompt or Weigh screen
+    fn read_scale(&mut self) -> io::Result<()> {
+        let Some(path) = self.config.scale.clone() else {
+            return Ok(());
+        };
+        let task = Task::spawn("Reading the scale", move || read_weight(&path));
+        self.reading = Some(task);
+        self.draw_reading()
+    }
+
+    /// draw the spinner for the scale being read in the status bar, hiding
+    /// the cursor until the reading is done
+    fn draw_reading(&mut self) -> io::Result<()> {
+        let Some(task) = &self.reading else {
+            return Ok(());
+        };
+        let text = format!(" {} {}... Esc Cancel ", task.spinner(), task.label);
+        let width = self.cols.saturating_sub(4) as usize;
+        self.queue(cursor::Hide)?;
+        self.move_to(2, self.frame().bottom())?;
+        let text = layout::truncate(&text, width);
+        self.write_colored(text, self.config.theme.accent)?;
+        self.flush()
+    }
+
+    /// put the weight read from the scale into the screen that asked for it
+    /// once it arrives, or otherwise advance the spinner
+    fn check_reading(&mut self) -> io::Result<()> {
+        let Some(result) = self.reading.as_ref().and_then(Task::poll) else {
+            return self.draw_reading();
+        };
+        self.reading = None;
+        match result {
+            Ok(weight) if self.state.is_weigh() => {
+                self.weigh = weight.to_string();
+            }
+            Ok(weight) => self.prompt.input = weight.to_string(),
+            Err(e) => self.set_status(e.to_string(), true),
+        }
+        self.render()
+    }
+
+    /// give up on reading the scale
+    fn cancel_reading(&mut self) -> io::Result<()> {
+        if let Some(task) = self.reading.take() {
+            self.set_status(format!("{}: cancelled", task.label), false);
+        }
+        self.render()
+    }
+
+    /// whether `Tab` reads the scale into the current prompt
+    fn prompt_uses_scale(&self) -> bool {
+        self.config.scale.is_some()
+            && matches!(self.prompt.action, PromptAction::Quantity(_))
+    }
+
+    fn render_prompt(&mut self) -> io::Result<()> {
+        self.draw_main()?;
+        self.draw_keys(State::Prompt)?;
+        // the prompt takes the place of the recent foods
+        let area = self.main_layout()[3].inner(2, 1);
+        let (y, width) = (area.bottom(), area.width as usize);
+        let blank = " ".repeat(width);
+        for y in area.y..=y {
+            self.move_to(area.x, y)?;
+            self.write_str(&blank)?;
+        }
+        if let Some(error) = &self.prompt.error {
+            let error = format!("{error:width$}");
+            self.move_to(area.x, y - 1)?;
+            self.write_colored(&error, self.config.theme.error)?;
+        } else if let PromptAction::Quantity(Food {
+            note: Some(note), ..
+        }) = &self.prompt.action
+        {
+            let note = format!("Note: {note}");
+            self.move_to(area.x, y - 1)?;
+            self.write_note(&format!("{note:width$}"))?;
+        }
+        // the label stays in place while the input scrolls past it
+        let label = layout::truncate(&self.prompt.label, width).to_owned();
+        let input = &self.prompt.input;
+        let (input, cursor) = layout::scroll(
+            input,
+            input.width(),
+            width.saturating_sub(label.width()),
+        );
+        self.move_to(area.x, y)?;
+        self.write_str(&format!("{label}{input}"))?;
+        self.move_to(area.x + (label.width() + cursor) as u16, y)?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    fn prompt_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Scale) => self.read_scale()?,
+            Some(Action::Accept) => self.accept_prompt()?,
+            Some(Action::Back) => self.back()?,
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.prompt.input.push(c);
+                    self.prompt.error = None;
+                    self.render_prompt()?;
+                }
+                KeyCode::Backspace => {
+                    self.prompt.input.pop();
+                    self.prompt.error = None;
+                    self.render_prompt()?;
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// act on the input of the current prompt and return to the main screen,
+    /// leaving the prompt open if the input is invalid
+    fn accept_prompt(&mut self) -> io::Result<()> {
+        let input = self.prompt.input.trim();
+        match self.prompt.action {
+            PromptAction::EntryTime => {
+                let Ok(time) = NaiveTime::parse_from_str(input, TIME_FMT)
+                else {
+                    let error =
+                        format!("expected a time like 08:30, got `{input}`");
+                    self.prompt.error = Some(error);
+                    return self.render_prompt();
+                };
+                if self.selected < self.log.day(self.date).len() {
+                    self.selected =
+                        self.log.set_time(self.date, self.selected, time);
+                    self.save_log()?;
+                }
+            }
+            PromptAction::EntryNote => {
+                let note = (!input.is_empty()).then(|| input.to_owned());
+                if let Some(e) = self.log.get_mut(self.date, self.selected) {
+                    e.note = note;
+                    self.save_log()?;
+                }
+            }
+            PromptAction::DayNote => {
+                self.log.set_day_note(self.date, input.to_owned());
+                self.save_log()?;
+            }
+            PromptAction::Quantity(ref food) => {
+                let quantity = if input.is_empty() {
+                    Err("enter a quantity, or Esc to cancel".to_owned())
+                } else {
+                    match parse::quantity(input, &food.unit) {
+                        Ok(quantity) if quantity <= 0.0 => {
+                            Err("must be more than 0".to_owned())
+                        }
+                        result => result,
+                    }
+                };
+                let quantity = match quantity {
+                    Ok(quantity) => quantity,
+                    Err(e) => {
+                        self.prompt.error = Some(e);
+                        return self.render_prompt();
+                    }
+                };
+                self.add_entries(vec![Entry::new(food.clone(), quantity)]);
+                self.save_log()?;
+            }
+            PromptAction::Consumed => {
+                let pct = match expr::eval(input) {
+                    Ok(pct) if pct > 0.0 && pct <= 100.0 => pct,
+                    Ok(_) => {
+                        let error = "must be more than 0 and at most 100";
+                        self.prompt.error = Some(error.to_owned());
+                        return self.render_prompt();
+                    }
+                    Err(e) => {
+                        self.prompt.error = Some(e.to_string());
+                        return self.render_prompt();
+                    }
+                };
+                if let Some(e) = self.log.get_mut(self.date, self.selected) {
+                    e.consumed = pct / 100.0;
+                    self.save_log()?;
+                }
+            }
+            PromptAction::DayGoals => {
+                let goals = if input.is_empty() {
+                    None
+                } else {
+                    match input.parse::<Goals>() {
+                        Ok(goals) => Some(goals),
+                        Err(e) => {
+                            self.prompt.error = Some(e);
+                            return self.render_prompt();
+                        }
+                    }
+                };
+                self.targets.set_override(self.date, goals);
+                self.targets.save()?;
+            }
+            PromptAction::Event => {
+                if !input.is_empty() {
+                    self.events.add(self.date, input);
+                    self.events.save()?;
+                }
+            }
+            PromptAction::Command => {
+                let command = input.to_owned();
+                return self.run_command(&command);
+            }
+        }
+        self.render_main()
+    }
+
+    /// prompt for a note on the selected entry
+    fn edit_entry_note(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(self.selected) else {
+            return Ok(());
+        };
+        let input = entry.note.clone().unwrap_or_default();
+        self.prompt("Note: ", input, PromptAction::EntryNote)
+    }
+
+    /// prompt for a note on the current day
+    fn edit_day_note(&mut self) -> io::Result<()> {
+        let input = self.log.day_note(self.date).unwrap_or("").to_owned();
+        self.prompt("Day note: ", input, PromptAction::DayNote)
+    }
+
+    /// prompt for a quantity of the `n`th most recently logged food
+    fn log_recent(&mut self, n: usize) -> io::Result<()> {
+        let Some(food) = self.log.recent_foods(n + 1).get(n).cloned() else {
+            return Ok(());
+        };
+        self.prompt_quantity(food.clone())
+    }
+
+    /// prompt for a quantity of `food` to log
+    fn prompt_quantity(&mut self, food: Food) -> io::Result<()> {
+        let label = format!("{} ({}): ", food.name, food.unit);
+        let input = self
+            .log
+            .usual_quantity(&food)
+            .map_or(String::new(), |q| q.to_string());
+        self.prompt(label, input, PromptAction::Quantity(food))
+    }
+
+    /// prompt for the percentage of the selected entry that was eaten
+    fn edit_consumed(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(self.selected) else {
+            return Ok(());
+        };
+        let input = format!("{}", 100.0 * entry.consumed);
+        self.prompt("Eaten (%): ", input, PromptAction::Consumed)
+    }
+
+    /// prompt for goals for the current day alone, overriding the defaults
+    fn edit_day_goals(&mut self) -> io::Result<()> {
+        let input = match self.targets.get_override(self.date) {
+            Some(goals) => goals.to_string(),
+            None => String::new(),
+        };
+        let label = "Day goals (calories protein carbs fat, - to skip): ";
+        self.prompt(label, input, PromptAction::DayGoals)
+    }
+
+    /// prompt for an event to record on the current day
+    fn add_event(&mut self) -> io::Result<()> {
+        self.prompt("Event: ", String::new(), PromptAction::Event)
+    }
+
+    /// prompt for a new time for the selected entry
+    fn edit_time(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(self.selected) else {
+            return Ok(());
+        };
+        let input = entry
+            .time
+            .map(|t| t.format(TIME_FMT).to_string())
+            .unwrap_or_default();
+        self.prompt("Time (HH:MM): ", input, PromptAction::EntryTime)
+    }
+
+    /// the width of the input boxes in the AddFood form, narrowed to fit the
+    /// labels and boxes inside the border of a small screen
+    fn input_width(&self) -> u16 {
+        INPUT_WIDTH
+            .min(self.cols.saturating_sub(MAX_WIDTH + 6))
+            .max(2)
+    }
+
+    /// return the position of the first label in the AddFood form
+    fn form_origin(&self) -> (u16, u16) {
+        // so we want to center the labels and input boxes in the width of the
+        // screen, and there are going to be 6 lines: 5 labels + accept
+        let width = MAX_WIDTH + self.input_width();
+        let x = (self.cols / 2).saturating_sub(width.div_ceil(2));
+        let y = (self.rows / 2)
+            .saturating_sub((3 * LABELS.len() + 1) as u16 / 2)
+            .max(1);
+        (x, y)
+    }
+
+    /// draw the queued batch of entries below the AddFood form, along with
+    /// the combined macros of the batch and the current form contents
+    fn draw_batch(&mut self) -> io::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let mut total = Macros::default();
+        for entry in &self.batch {
+            total += entry.macros();
+        }
+        let mut n = self.batch.len();
+        if let Some(entry) = self.form_entry() {
+            total += entry.macros();
+            n += 1;
+        }
+        let width = (MAX_WIDTH + 1 + self.input_width()) as usize;
+        let summary = format!(
+            "{n} items: Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            total.calories, total.protein, total.carbs, total.fat
+        );
+        let names = self
+            .batch
+            .iter()
+            .map(|e| e.food.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (x, y) = self.form_origin();
+        let y = y + 3 * LABELS.len() as u16 - 1;
+        for (i, line) in [summary, names].iter().enumerate() {
+            let line: String = line.chars().take(width).collect();
+            self.move_to(x, y + i as u16)?;
+            self.write_str(&format!("{line:width$}"))?;
+        }
+        Ok(())
+    }
+
+    /// draw the date that the form's entries will be logged to above the form
+    fn draw_form_date(&mut self) -> io::Result<()> {
+        let (x, y) = self.form_origin();
+        let date = if self.date == Local::now().date_naive() {
+            format!("Logging to today ({})", self.date.format(DATE_FMT))
+        } else {
+            format!("Logging to {}", self.date.format(DATE_FMT))
+        };
+        let width = (MAX_WIDTH + 1 + self.input_width()) as usize;
+        self.move_to(x, y.saturating_sub(3).max(1))?;
+        self.write_str(&format!("{date:width$}"))?;
+        Ok(())
+    }
+
+    fn add_food(&mut self) -> io::Result<()> {
+        self.set_state(State::AddFood);
+        self.candidate = None;
+        self.form_field = 0;
+        self.render_form()
+    }
+
+    /// show the AddFood form relabeled for logging a portion of a dish
+    fn split_dish(&mut self) -> io::Result<()> {
+        self.set_state(State::SplitDish);
+        self.form_field = 0;
+        self.render_form()
+    }
+
+    /// what is wrong with the contents of `field` in the form, if anything.
+    /// the units of a food and the weight of a dish may be left empty
+    fn field_error(&self, field: usize) -> Option<String> {
+        let text = self.buf[field].trim();
+        if text.is_empty() {
+            return (field != 5).then(|| "required".to_owned());
+        }
+        let dish = self.state.is_split_dish();
+        let number = |s| expr::eval(s).map(drop).map_err(|e| e.to_string());
+        let positive = |s| match expr::eval(s) {
+            Ok(n) if n <= 0.0 => Err("must be more than 0".to_owned()),
+            n => n.map(drop).map_err(|e| e.to_string()),
+        };
+        match field {
+            1..=4 => number(text),
+            5 | 6 if dish => positive(text),
+            6 => parse::quantity(text, &self.buf[5]).map(drop),
+            _ => Ok(()),
+        }
+        .err()
+    }
+
+    /// check the field being left, unless it was left empty
+    fn leave_field(&mut self) {
+        let field = self.form_field;
+        self.form_checked[field] = !self.buf[field].is_empty();
+    }
+
+    /// check every field of the form, moving to the first invalid one if there
+    /// are any. returns whether they are all valid
+    fn check_form(&mut self) -> bool {
+        self.form_checked = [true; 7];
+        match (0..self.buf.len()).find(|&i| self.field_error(i).is_some()) {
+            Some(field) => {
+                self.form_field = field;
+                self.candidate = None;
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// whether every field of the form is empty
+    fn form_empty(&self) -> bool {
+        self.buf.iter().all(|input| input.is_empty())
+    }
+
+    /// add the contents of each field of the form to its history
+    fn remember_form(&mut self) {
+        self.buf.iter_mut().for_each(Input::remember);
+    }
+
+    fn clear_form(&mut self) {
+        self.buf.iter_mut().for_each(Input::clear);
+        self.form_checked = [false; 7];
+        self.form_field = 0;
+    }
+
+    /// the checked fields of the form with errors, and their errors
+    fn form_errors(&self) -> Vec<(usize, String)> {
+        (0..self.buf.len())
+            .filter(|&i| self.form_checked[i])
+            .filter_map(|i| Some((i, self.field_error(i)?)))
+            .collect()
+    }
+
+    /// the entry described by the current contents of the form, if they are
+    /// valid
+    fn form_entry(&self) -> Option<Entry> {
+        if self.state.is_split_dish() {
+            let DishPortion(food, n) = DishPortion::try_from(&self.buf).ok()?;
+            Some(Entry::new(food, n))
+        } else {
+            let FoodQuantity(food, n) =
+                FoodQuantity::try_from(&self.buf).ok()?;
+            Some(Entry::new(food, n))
+        }
+    }
+
+    fn render_form(&mut self) -> io::Result<()> {
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::AddFood)?;
+
+        // the idea here is to replicate an HTML form essentially:
+        //
+        // Food Name: [___________________]
+        //  Calories: [___________________]
+        //
+        // and so on, with Tab moving between the fields. We'll also need to
+        // show the cursor again here. Basics are actually easy, showing the
+        // completion candidates will be most of the work.
+
+        self.draw_batch()?;
+        self.draw_form_date()?;
+
+        self.draw_candidates()?;
+        self.queue(cursor::Show)?;
+
+        self.flush()?;
+        Ok(())
+    }
+
+    /// write `s` at the cursor in `color`
+    fn write_colored(&mut self, s: &str, color: Color) -> io::Result<()> {
+        self.queue(SetForegroundColor(color))?;
+        self.write_str(s)?;
+        self.queue(ResetColor)?;
+        Ok(())
+    }
+
+    /// write `s` at the cursor in the color of food notes
+    fn write_note(&mut self, s: &str) -> io::Result<()> {
+        self.write_colored(s, self.config.theme.note)
+    }
+
+    /// draw the note of the food named in the AddFood form, if any, between
+    /// the date and the form
+    fn draw_form_note(&mut self) -> io::Result<()> {
+        let (x, y) = self.form_origin();
+        let width = (MAX_WIDTH + 1 + self.input_width()) as usize;
+        let note = self
+            .foods
+            .iter()
+            .find(|food| food.name == *self.buf[0])
+            .and_then(|food| food.note.as_ref())
+            .filter(|_| self.state.is_add_food())
+            .map_or(String::new(), |note| format!("Note: {note}"));
+        let note: String = note.chars().take(width).collect();
+        self.move_to(x, y.saturating_sub(2).max(1))?;
+        self.write_note(&format!("{note:width$}"))
+    }
+
+    /// draw the labels, boxes, and contents of the form's fields
+    fn draw_fields(&mut self) -> io::Result<()> {
+        self.draw_form_note()?;
+        let (x, y) = self.form_origin();
+        let labels = if self.state.is_split_dish() {
+            DISH_LABELS
+        } else {
+            LABELS
+        };
+        let errors = self.form_errors();
+        for (i, label) in labels.iter().enumerate() {
+            let (field, _) = self.field_view(i);
+            let invalid = errors.iter().any(|(e, _)| *e == i);
+            let i = 3 * i as u16;
+            self.move_to(x, y + i)?;
+            self.write_str(label)?;
+            let input = Rect::new(
+                x + MAX_WIDTH + 1,
+                y + i - 1,
+                self.input_width() + 1,
+                3,
+            );
+            if invalid {
+                self.queue(SetForegroundColor(self.config.theme.error))?;
+            }
+            self.draw_block(input)?;
+            self.queue(ResetColor)?;
+            self.move_to(x + MAX_WIDTH + 2, y + i)?;
+            self.write_str(&field)?;
+        }
+        // explain the error in the current field, or else the first one, over
+        // the note
+        let Some((e, error)) = errors
+            .iter()
+            .find(|(e, _)| *e == self.form_field)
+            .or(errors.first())
+        else {
+            return Ok(());
+        };
+        let label = labels[*e].trim().trim_end_matches(':');
+        let width = (MAX_WIDTH + 1 + self.input_width()) as usize;
+        self.move_to(x, y.saturating_sub(2).max(1))?;
+        let error = fit(&format!("{label}: {error}"), width);
+        self.write_colored(&error, self.config.theme.error)
+    }
+
+    /// the foods best matching the name typed in the AddFood form
+    fn candidates(&self) -> Vec<&Food> {
+        if !self.state.is_add_food() || self.buf[0].is_empty() {
+            return Vec::new();
+        }
+        self.matching_foods(&self.buf[0], CANDIDATES)
+    }
+
+    /// the at most `n` foods best matching `query`, favoring those logged
+    /// often and recently
+    fn matching_foods(&self, query: &str, n: usize) -> Vec<&Food> {
+        let today = Local::now().date_naive();
+        let Some(query) = Query::new(self.search_mode, query) else {
+            return Vec::new();
+        };
+        search::best(&self.index, &query, &self.foods, &self.usage, today, n)
+    }
+
+    /// save the changes to the log, refreshing [Tui::usage] with them
+    fn save_log(&mut self) -> io::Result<()> {
+        self.usage = self.log.usage();
+        self.log.save()
+    }
+
+    /// the indices of the characters in `name` matched by `query`, shifted by
+    /// `offset` for where the name starts in its line
+    fn matched(&self, query: &str, name: &str, offset: usize) -> Vec<usize> {
+        Query::new(self.search_mode, query).map_or(Vec::new(), |query| {
+            query.positions(name).iter().map(|i| i + offset).collect()
+        })
+    }
+
+    /// write `line`, highlighting the characters at the indices in `matched`
+    fn write_matched(
+        &mut self,
+        line: &str,
+        matched: &[usize],
+    ) -> io::Result<()> {
+        for (i, c) in line.chars().enumerate() {
+            if matched.contains(&i) {
+                let mut buf = [0; 4];
+                let c = c.encode_utf8(&mut buf);
+                self.write_colored(c, self.config.theme.matched)?;
+            } else {
+                self.write_str(c.encode_utf8(&mut [0; 4]))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// draw `lines` down `area` like [Tui::draw_lines], highlighting the
+    /// characters of each at the indices paired with it
+    fn draw_matched(
+        &mut self,
+        area: Rect,
+        lines: &[(String, Vec<usize>)],
+    ) -> io::Result<()> {
+        let width = area.width as usize;
+        let lines = lines.iter().take(area.height as usize);
+        for (i, (line, matched)) in lines.enumerate() {
+            self.move_to(area.x, area.y + i as u16)?;
+            self.write_matched(layout::truncate(line, width), matched)?;
+        }
+        Ok(())
+    }
+
+    /// the text of the form field `i` as it fits in its box and the column of
+    /// the cursor in it, scrolled so that the cursor of the field being edited
+    /// stays inside the box
+    fn field_view(&self, i: usize) -> (String, usize) {
+        let width = self.input_width() as usize - 1;
+        let cursor = if i == self.form_field {
+            self.buf[i].cursor_width()
+        } else {
+            0
+        };
+        layout::scroll(&self.buf[i], cursor, width)
+    }
+
+    /// move the cursor to its place in the form field being edited
+    fn place_form_cursor(&mut self) -> io::Result<()> {
+        let (x, y) = self.form_origin();
+        let right = self.field_view(self.form_field).1 as u16;
+        let field = self.form_field as u16;
+        self.move_to(x + MAX_WIDTH + 2 + right, y + 3 * field)
+    }
+
+    /// redraw the form's fields and, while the name field is active, list the
+    /// foods matching the typed name below it, over the fields that follow
+    fn draw_candidates(&mut self) -> io::Result<()> {
+        let field = self.form_field;
+        self.draw_fields()?;
+        let (x, y) = self.form_origin();
+        let lines: Vec<(String, Vec<usize>)> = if field == 0 {
+            self.candidates()
+                .iter()
+                .enumerate()
+                .map(|(i, food)| {
+                    let arrow =
+                        if self.candidate == Some(i) { '>' } else { ' ' };
+                    (
+                        format!("{arrow} {} ({})", food.name, food.unit),
+                        self.matched(&self.buf[0], &food.name, 2),
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        // the details of the highlighted food, below a rule under the list
+        let details = match self.candidate {
+            Some(i) if field == 0 => self
+                .candidates()
+                .get(i)
+                .map_or(Vec::new(), |food| food.details()),
+            _ => Vec::new(),
+        };
+        let width = self.input_width() as usize - 1;
+        let details: Vec<(String, Vec<usize>)> = details
+            .into_iter()
+            .map(|line| (format!(" {line}"), Vec::new()))
+            .collect();
+        let rule = (self.border.line(width), Vec::new());
+        let lines: Vec<(String, Vec<usize>)> = if details.is_empty() {
+            lines
+        } else {
+            lines.into_iter().chain([rule]).chain(details).collect()
+        };
+        if !lines.is_empty() {
+            // hang the list off of the bottom of the name box
+            let x = x + MAX_WIDTH + 1;
+            let b = self.border;
+            let line = b.line(width);
+            self.move_to(x, y + 1)?;
+            self.write_str(&format!("{}{line}{}", b.left_tee, b.right_tee))?;
+            let side = b.vertical.to_string();
+            for (i, (line, matched)) in lines.iter().enumerate() {
+                self.move_to(x, y + 2 + i as u16)?;
+                self.write_str(&side)?;
+                self.write_matched(&fit(line, width), matched)?;
+                self.write_str(&side)?;
+            }
+            self.move_to(x, y + 2 + lines.len() as u16)?;
+            let (left, right) = (b.bottom_left, b.bottom_right);
+            self.write_str(&format!("{left}{line}{right}"))?;
+        }
+        self.place_form_cursor()
+    }
+
+    /// fill in the rest of the form from the highlighted candidate, leaving
+    /// the cursor in the Quantity field with the quantity of it usually logged
+    fn select_candidate(&mut self) -> io::Result<()> {
+        let Some(food) = self
+            .candidate
+            .take()
+            .and_then(|i| self.candidates().get(i).copied().cloned())
+        else {
+            return Ok(());
+        };
+        let quantity = self.log.usual_quantity(&food);
+        self.buf[6].set(quantity.map_or(String::new(), |q| q.to_string()));
+        self.buf[0].set(food.name);
+        self.buf[1].set(food.calories.to_string());
+        self.buf[2].set(food.carbs.to_string());
+        self.buf[3].set(food.fat.to_string());
+        self.buf[4].set(food.protein.to_string());
+        self.buf[5].set(food.unit);
+        self.form_checked = [false; 7];
+        self.form_field = self.buf.len() - 1;
+        self.draw_candidates()?;
+        self.update_batch()?;
+        self.flush()
+    }
+
+    fn food_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> Result<(), io::Error> {
+        let field = self.form_field;
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            // the arrows pick from the matching foods unless there are none or
+            // an earlier name is already being recalled
+            Some(Action::Select)
+                if field == 0
+                    && !self.buf[0].is_recalling()
+                    && !self.candidates().is_empty() =>
+            {
+                let n = self.candidates().len();
+                self.candidate = Some(match (self.candidate, event.code) {
+                    (None, KeyCode::Down) => 0,
+                    (None, _) => n - 1,
+                    (Some(i), KeyCode::Down) => (i + 1) % n,
+                    (Some(i), _) => (i + n - 1) % n,
+                });
+                self.draw_candidates()?;
+                self.flush()?;
+            }
+            Some(Action::Next) if field == 0 && self.candidate.is_some() => {
+                self.select_candidate()?;
+            }
+            Some(Action::SearchMode) if field == 0 => {
+                self.search_mode = self.search_mode.next();
+                self.candidate = None;
+                self.render_form()?;
+            }
+            Some(Action::Queue) => {
+                if !self.check_form() {
+                    return self.render_form();
+                }
+                if let Some(entry) = self.form_entry() {
+                    self.batch.push(entry);
+                    self.remember_form();
+                    self.clear_form();
+                    self.render_form()?;
+                }
+            }
+            // Enter moves on like Tab until the last field, so that a half
+            // filled form isn't submitted by accident. an empty one still
+            // submits the batch
+            Some(Action::Next)
+                if field < self.buf.len() - 1
+                    && (event.code == KeyCode::Tab || !self.form_empty()) =>
+            {
+                self.leave_field();
+                self.form_field += 1;
+                self.draw_candidates()?;
+                self.flush()?;
+            }
+            Some(Action::Prev) if field > 0 => {
+                self.leave_field();
+                self.form_field -= 1;
+                self.draw_candidates()?;
+                self.flush()?;
+            }
+            Some(Action::Day) => {
+                let days = if event.code == KeyCode::PageUp { -1 } else { 1 };
+                self.shift_date(days);
+                self.draw_form_date()?;
+                self.place_form_cursor()?;
+                self.flush()?;
+            }
+            Some(Action::Next | Action::Submit)
+                if event.code != KeyCode::Tab =>
+            {
+                // an empty form just submits the batch
+                if !self.form_empty() && !self.check_form() {
+                    return self.render_form();
+                }
+                if let Some(entry) = self.form_entry() {
+                    // TODO also store the food in the database
+                    self.batch.push(entry);
+                    self.remember_form();
+                }
+                let batch = std::mem::take(&mut self.batch);
+                self.add_entries(batch);
+                self.save_log()?;
+                self.clear_form();
+                self.render_main()?;
+            }
+            Some(Action::Back) => {
+                // discard everything typed so the next form starts empty
+                self.batch.clear();
+                self.clear_form();
+                self.candidate = None;
+                self.back()?;
+            }
+            _ => {
+                if self.buf[field].handle(event) {
+                    self.form_checked[field] = false;
+                    self.candidate = None;
+                    self.draw_candidates()?;
+                    self.update_batch()?;
+                    self.flush()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// insert pasted `text` into the input of the current screen. in the
+    /// AddFood form, text split by tabs or lines, as copied from a table of
+    /// nutrition facts, fills the current field and those after it in turn,
+    /// and units like the `g` in `12 g` are dropped from the macros
+    fn paste(&mut self, text: &str) -> io::Result<()> {
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match self.state {
+            State::AddFood | State::SplitDish => {
+                let parts = text
+                    .split(['\t', '\n', '\r'])
+                    .map(str::trim)
+                    .filter(|part| !part.is_empty());
+                let start = self.form_field;
+                for (field, part) in (start..self.buf.len()).zip(parts) {
+                    let part = if (1..=4).contains(&field) {
+                        part.trim_end_matches(|c: char| {
+                            c.is_alphabetic() || c.is_whitespace()
+                        })
+                    } else {
+                        part
+                    };
+                    if field == start {
+                        self.buf[field].insert_str(part);
+                    } else {
+                        self.buf[field].set(part);
+                    }
+                    self.form_checked[field] = false;
+                    self.form_field = field;
+                }
+                self.candidate = None;
+                self.render_form()
+            }
+            State::Prompt => {
+                self.prompt.input.push_str(&line);
+                self.prompt.error = None;
+                self.render_prompt()
+            }
+            State::QuickAdd => {
+                self.quick.push_str(&line);
+                self.render_quick_add()
+            }
+            State::History => {
+                self.history.push_str(&line);
+                self.history_cursor = 0;
+                self.render_history()
+            }
+            State::Search => {
+                self.search.push_str(&line);
+                self.search_cursor = 0;
+                self.render_search()
+            }
+            State::FoodCompare => {
+                let pane = self.food_compare_pane;
+                self.food_compare[pane].push_str(&line);
+                self.food_compare_cursor[pane] = 0;
+                self.render_food_compare()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// handle a mouse event. clicking a pane of the main screen or a field of
+    /// the AddFood form focuses it, and scrolling moves through the lists of
+    /// entries and foods as the Up and Down keys do
+    fn mouse(&mut self, event: MouseEvent) -> io::Result<()> {
+        let code = match event.kind {
+            MouseEventKind::ScrollUp => KeyCode::Up,
+            MouseEventKind::ScrollDown => KeyCode::Down,
+            MouseEventKind::Down(MouseButton::Left) if self.state.is_main() => {
+                return self.click_pane(event.column, event.row);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                return self.click_field(event.column, event.row);
+            }
+            _ => return Ok(()),
+        };
+        let key = KeyEvent::new(code, KeyModifiers::NONE);
+        match self.state {
+            State::Main => {
+                self.select(if code == KeyCode::Up { -1 } else { 1 })
+            }
+            State::AddFood if self.form_field == 0 => self.food_form(key),
+            State::History => self.history_form(key),
+            State::FoodCompare => self.food_compare_form(key),
+            State::Search => self.search_form(key),
+            _ => Ok(()),
+        }
+    }
+
+    /// focus the pane of the main screen drawn at (`col`, `row`), if any
+    fn click_pane(&mut self, col: u16, row: u16) -> io::Result<()> {
+        let [_, entries, totals, recent] = self.main_layout();
+        let panes = [
+            (entries, Pane::Entries),
+            (totals, Pane::Totals),
+            (recent, Pane::Recent),
+        ];
+        let Some((_, pane)) = panes.into_iter().find(|(area, _)| {
+            (area.x..=area.right()).contains(&col)
+                && (area.y..=area.bottom()).contains(&row)
+        }) else {
+            return Ok(());
+        };
+        self.pane = pane;
+        self.render_main()
+    }
+
+    /// focus the field of the AddFood form drawn at (`col`, `row`), if any
+    fn click_field(&mut self, col: u16, row: u16) -> io::Result<()> {
+        if !self.state.is_add_food() && !self.state.is_split_dish() {
+            return Ok(());
+        }
+        let (x, y) = self.form_origin();
+        let right = x + MAX_WIDTH + 1 + self.input_width();
+        if col < x || col > right || row + 1 < y {
+            return Ok(());
+        }
+        let field = (row + 1 - y) as usize / 3;
+        if field >= self.buf.len() || field == self.form_field {
+            return Ok(());
+        }
+        self.leave_field();
+        self.form_field = field;
+        self.candidate = None;
+        self.draw_candidates()?;
+        self.flush()
+    }
+
+    /// redraw the batch preview after the form contents change, returning the
+    /// cursor to its position in the current field
+    fn update_batch(&mut self) -> io::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        self.draw_batch()?;
+        self.place_form_cursor()
+    }
+
+    fn quick_add(&mut self) -> io::Result<()> {
+        self.set_state(State::QuickAdd);
+        self.quick.clear();
+        self.render_quick_add()
+    }
+
+    /// draw the QuickAdd input line and what each of its items resolves to
+    fn render_quick_add(&mut self) -> io::Result<()> {
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::QuickAdd)?;
+
+        let [title, list] = self.title_layout();
+        let lines: Vec<String> = parse_entries(&self.quick, &self.foods)
+            .iter()
+            .map(|r| match &r.result {
+                Ok(entry) => format!("✓ {}: {entry}", r.input),
+                Err(e) => format!("✗ {}: {e}", r.input),
+            })
+            .collect();
+        self.draw_lines(list, &lines)?;
+
+        let prompt = format!("Add: {}", self.quick);
+        self.draw_lines(title, &[prompt])?;
+        self.queue(cursor::Show)?;
+        self.flush()?;
+        Ok(())
+    }
+
+    fn quick_add_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Accept) => {
+                // only log anything once every item has been resolved
+                let resolved: Result<Vec<Entry>, String> =
+                    parse_entries(&self.quick, &self.foods)
+                        .into_iter()
+                        .map(|r| r.result)
+                        .collect();
+                let entries = match resolved {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        self.set_status(e, true);
+                        return self.render_quick_add();
+                    }
+                };
+                if let Some(i) = self.resolving.take() {
+                    // log to the time the capture was written down rather
+                    // than now, and clear it from the inbox
+                    let Capture { stamp, .. } = self.inbox.items.remove(i);
+                    for mut entry in entries {
+                        entry.time = Some(stamp.time());
+                        self.log.add(stamp.date(), entry);
+                    }
+                    self.save_log()?;
+                    self.inbox.save()?;
+                    self.render_inbox()?;
+                } else {
+                    self.add_entries(entries);
+                    self.save_log()?;
+                    self.render_main()?;
+                }
+            }
+            Some(Action::Back) => {
+                self.resolving = None;
+                self.back()?;
+            }
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.quick.push(c);
+                    self.render_quick_add()?;
+                }
+                KeyCode::Backspace => {
+                    self.quick.pop();
+                    self.render_quick_add()?;
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    fn render_inbox(&mut self) -> io::Result<()> {
+        self.set_state(State::Inbox);
+        let n = self.inbox.items.len();
+        self.inbox_cursor = self.inbox_cursor.min(n.saturating_sub(1));
+
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Inbox)?;
+
+        let [title, list] = self.title_layout();
+        self.draw_title(title, &format!("Inbox: {n} unresolved"))?;
+        let items: Vec<String> = self
+            .inbox
+            .items
+            .iter()
+            .map(|item| format!("{}  {}", item.format_stamp(), item.text))
+            .collect();
+        self.draw_list(list, &items, Some(self.inbox_cursor))?;
+        self.flush()
+    }
+
+    /// collect the captures and the recent entries that are estimated or look
+    /// wrong and show them on the Review screen
+    fn review(&mut self) -> io::Result<()> {
+        self.review.clear();
+        for i in 0..self.inbox.items.len() {
+            self.review.push(ReviewItem::Capture(i));
+        }
+        let today = Local::now().date_naive();
+        let from = today - Days::new(REVIEW_DAYS);
+        for (date, entries) in self.log.range(from, today) {
+            for (i, entry) in entries.iter().enumerate() {
+                let mut reasons = entry.problems();
+                if entry.estimated {
+                    reasons.insert(0, "estimated");
+                }
+                if !reasons.is_empty() {
+                    let why = format!("{entry}: {}", reasons.join(", "));
+                    self.review.push(ReviewItem::Entry(date, i, why));
+                }
+            }
+        }
+        self.review_cursor = 0;
+        self.render_review()
+    }
+
+    fn render_review(&mut self) -> io::Result<()> {
+        self.set_state(State::Review);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Review)?;
+
+        let [title, list] = self.title_layout();
+        let heading = format!(
+            "Review: {} items from the inbox and the last {REVIEW_DAYS} days",
+            self.review.len()
+        );
+        self.draw_title(title, &heading)?;
+
+        let lines: Vec<String> = self
+            .review
+            .iter()
+            .map(|item| match item {
+                ReviewItem::Capture(i) => {
+                    let c = &self.inbox.items[*i];
+                    format!("{}  inbox: {}", c.format_stamp(), c.text)
+                }
+                ReviewItem::Entry(date, _, why) => {
+                    format!("{}        {why}", date.format(DATE_FMT))
+                }
+            })
+            .collect();
+        self.draw_list(list, &lines, Some(self.review_cursor))?;
+        self.flush()
+    }
+
+    /// walk through the day's entries from the selected one, replacing rough
+    /// quantities with weighed ones
+    fn weigh(&mut self) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(self.selected) else {
+            return Ok(());
+        };
+        self.weigh = entry.quantity.to_string();
+        self.render_weigh()
+    }
+
+    fn render_weigh(&mut self) -> io::Result<()> {
+        self.set_state(State::Weigh);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Weigh)?;
+
+        // the totals as they would be with the quantity being typed
+        let entries = self.log.day(self.date).to_vec();
+        let mut total = Macros::default();
+        for (i, entry) in entries.iter().enumerate() {
+            let mut entry = entry.clone();
+            if i == self.selected {
+                entry.quantity = parse::quantity(&self.weigh, &entry.food.unit)
+                    .unwrap_or(0.0);
+            }
+            total += entry.macros();
+        }
+        use Constraint::*;
+        let [title, totals, _, list] = self.body().split(
+            Direction::Vertical,
+            [Length(1), Length(1), Length(1), Fill],
+        );
+        let heading = "Enter the weighed quantity of each entry".to_owned();
+        let totals_line = format!(
+            "Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            total.calories, total.protein, total.carbs, total.fat
+        );
+        self.draw_title(title, &heading)?;
+        self.draw_lines(totals, &[totals_line])?;
+
+        let items: Vec<String> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                if i == self.selected {
+                    format!(
+                        "{} {} {}",
+                        self.weigh, entry.food.unit, entry.food.name
+                    )
+                } else {
+                    entry.to_string()
+                }
+            })
+            .collect();
+        self.draw_list(list, &items, Some(self.selected))?;
+        // leave the cursor after the quantity being typed
+        let start = (self.selected + 1).saturating_sub(list.height as usize);
+        let x = list.x + 2 + self.weigh.width() as u16;
+        self.move_to(x, list.y + (self.selected - start) as u16)?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    /// move the Weigh screen to entry `i`, starting from its current quantity
+    fn weigh_entry(&mut self, i: usize) -> io::Result<()> {
+        let Some(entry) = self.log.day(self.date).get(i) else {
+            return self.render_main();
+        };
+        self.weigh = entry.quantity.to_string();
+        self.selected = i;
+        self.render_weigh()
+    }
+
+    fn weigh_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Scale) => {
+                self.read_scale()?;
+            }
+            Some(Action::Select) if event.code == KeyCode::Up => {
+                if self.selected > 0 {
+                    self.weigh_entry(self.selected - 1)?;
+                }
+            }
+            Some(Action::Select) => {
+                let n = self.log.day(self.date).len();
+                if self.selected + 1 < n {
+                    self.weigh_entry(self.selected + 1)?;
+                }
+            }
+            Some(Action::Accept) => {
+                let Some(entry) = self.log.day(self.date).get(self.selected)
+                else {
+                    return Ok(());
+                };
+                let quantity =
+                    match parse::quantity(&self.weigh, &entry.food.unit) {
+                        Ok(q) if q <= 0.0 => {
+                            Err("quantity must be more than 0".to_owned())
+                        }
+                        result => result,
+                    };
+                let quantity = match quantity {
+                    Ok(quantity) => quantity,
+                    Err(e) => {
+                        self.set_status(format!("Quantity: {e}"), true);
+                        return self.render_weigh();
+                    }
+                };
+                if let Some(e) = self.log.get_mut(self.date, self.selected) {
+                    e.quantity = quantity;
+                    // a weighed quantity is no longer an estimate
+                    e.estimated = false;
+                    self.save_log()?;
+                }
+                self.weigh_entry(self.selected + 1)?;
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.weigh.push(c);
+                    self.render_weigh()?;
+                }
+                KeyCode::Backspace => {
+                    self.weigh.pop();
+                    self.render_weigh()?;
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    fn numpad(&mut self) -> io::Result<()> {
+        self.numpad = Default::default();
+        self.numpad_field = 0;
+        self.render_numpad()
+    }
+
+    /// the food chosen by number on the Numpad screen, if any
+    fn numpad_food(&self) -> Option<&Food> {
+        let n: usize = self.numpad[0].parse().ok()?;
+        self.foods.get(n.checked_sub(1)?)
+    }
+
+    /// draw the Numpad screen: a food number and quantity field, each advanced
+    /// with Enter or `.`, above the numbered food database
+    fn render_numpad(&mut self) -> io::Result<()> {
+        self.set_state(State::Numpad);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Numpad)?;
+
+        let food = match self.numpad_food() {
+            Some(food) => format!("{} ({})", food.name, food.unit),
+            None => String::new(),
+        };
+        let fields = [
+            format!("   Food #: {}", self.numpad[0]),
+            format!(" Quantity: {}", self.numpad[1]),
+        ];
+        use Constraint::*;
+        let [food_row, quantity_row, _, list] = self.body().split(
+            Direction::Vertical,
+            [Length(1), Length(1), Length(1), Fill],
+        );
+        self.draw_lines(food_row, &[format!("{}   {food}", fields[0])])?;
+        if let Some(note) = self.numpad_food().and_then(|f| f.note.clone()) {
+            self.write_note(&format!("  Note: {note}"))?;
+        }
+        self.draw_lines(quantity_row, &[fields[1].clone()])?;
+
+        // list the foods around the one being typed
+        let max = list.height as usize;
+        let chosen = self.numpad[0].parse::<usize>().unwrap_or(1);
+        let start = chosen.saturating_sub(max / 2).max(1);
+        let foods: Vec<String> = self
+            .foods
+            .iter()
+            .enumerate()
+            .skip(start - 1)
+            .take(max)
+            .map(|(i, food)| {
+                let line =
+                    format!("{:>4} {} ({})", i + 1, food.name, food.unit);
+                match self.density(food) {
+                    Some(d) => format!("{line} [{d:.1}]"),
+                    None => line,
+                }
+            })
+            .collect();
+        self.draw_lines(list, &foods)?;
+
+        let field = self.numpad_field;
+        let x = list.x + fields[field].chars().count() as u16;
+        self.move_to(x, food_row.y + field as u16)?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    fn numpad_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let field = self.numpad_field;
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            // food numbers are whole, so `.` can double as Tab there
+            Some(Action::Next | Action::Accept) if field == 0 => {
+                self.numpad_field = 1;
+                self.render_numpad()?;
+            }
+            Some(Action::Accept) => {
+                let Some(food) = self.numpad_food().cloned() else {
+                    let text = format!("no food numbered `{}`", self.numpad[0]);
+                    self.set_status(text, true);
+                    return self.render_numpad();
+                };
+                let quantity = match expr::eval(&self.numpad[1]) {
+                    Ok(quantity) if quantity <= 0.0 => {
+                        Err("must be more than 0".into())
+                    }
+                    result => result,
+                };
+                let quantity = match quantity {
+                    Ok(quantity) => quantity,
+                    Err(e) => {
+                        self.set_status(format!("Quantity: {e}"), true);
+                        return self.render_numpad();
+                    }
+                };
+                self.add_entries(vec![Entry::new(food, quantity)]);
+                self.save_log()?;
+                self.numpad()?;
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => match event.code {
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => {
+                    self.numpad[field].push(c);
+                    self.render_numpad()?;
+                }
+                KeyCode::Backspace => {
+                    if self.numpad[field].pop().is_none() && field > 0 {
+                        self.numpad_field -= 1;
+                    }
+                    self.render_numpad()?;
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// compare the current day with the day before it
+    fn compare(&mut self) -> io::Result<()> {
+        self.compare = self.date.pred_opt().unwrap_or(self.date);
+        self.render_compare()
+    }
+
+    /// draw `date`'s totals and entries in the column `area`, marking the
+    /// totals that differ from `other` by more than a tenth
+    fn draw_column(
+        &mut self,
+        area: Rect,
+        date: NaiveDate,
+        other: NaiveDate,
+    ) -> io::Result<()> {
+        use Constraint::*;
+        let [title, _, totals_area, _, list] = area.split(
+            Direction::Vertical,
+            [Length(1), Length(1), Length(4), Length(1), Fill],
+        );
+        let m = self.log.totals(date);
+        let o = self.log.totals(other);
+        let heading = date.format("%a %Y-%m-%d").to_string();
+        self.draw_title(title, &heading)?;
+        let totals = [
+            ("Calories", m.calories, o.calories),
+            ("Protein", m.protein, o.protein),
+            ("Carbs", m.carbs, o.carbs),
+            ("Fat", m.fat, o.fat),
+        ];
+        for (i, (name, v, other)) in totals.into_iter().enumerate() {
+            self.move_to(totals_area.x, totals_area.y + i as u16)?;
+            let line = format!("{name:>8}: {v:>5.0} ({:+.0})", v - other);
+            if (v - other).abs() > 0.1 * v.max(other) {
+                self.write_colored(&line, self.config.theme.changed)?;
+            } else {
+                self.write_str(&line)?;
+            }
+        }
+        let entries: Vec<String> =
+            self.log.day(date).iter().map(Entry::to_string).collect();
+        self.draw_lines(list, &entries)
+    }
+
+    /// the body split into two columns with a gap between them
+    fn columns(&self) -> [Rect; 2] {
+        use Constraint::*;
+        let [left, _, right] = self
+            .body()
+            .split(Direction::Horizontal, [Fill, Length(2), Fill]);
+        [left, right]
+    }
+
+    /// draw the current day and the compared day side by side
+    fn render_compare(&mut self) -> io::Result<()> {
+        self.set_state(State::Compare);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Compare)?;
+        let [left, right] = self.columns();
+        let (date, other) = (self.date, self.compare);
+        self.draw_column(left, date, other)?;
+        self.draw_column(right, other, date)?;
+        self.flush()
+    }
+
+    fn compare_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        match self.action(event) {
+            Some(Action::Help) => return self.help(),
+            Some(Action::Select) if event.code == KeyCode::Up => {
+                self.shift_date(-1)
+            }
+            Some(Action::Select) => self.shift_date(1),
+            Some(Action::Day) if event.code == KeyCode::Left => {
+                self.compare = self.compare.pred_opt().unwrap_or(self.compare)
+            }
+            Some(Action::Day) => {
+                self.compare = self.compare.succ_opt().unwrap_or(self.compare)
+            }
+            Some(Action::Back) => return self.back(),
+            _ => return Ok(()),
+        }
+        self.render_compare()
+    }
+
+    fn history(&mut self) -> io::Result<()> {
+        self.history.clear();
+        self.history_cursor = 0;
+        self.render_history()
+    }
+
+    /// the logged entries matching the History screen's search
+    fn history_matches(&self) -> Vec<(NaiveDate, usize, &Entry)> {
+        Query::new(self.search_mode, &self.history)
+            .map_or_else(Vec::new, |query| self.log.search(&query))
+    }
+
+    fn render_history(&mut self) -> io::Result<()> {
+        self.set_state(State::History);
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::History)?;
+
+        let matches: Vec<String> = self
+            .history_matches()
+            .iter()
+            .map(|(date, _, entry)| {
+                let time = entry.time.map_or("     ".to_owned(), |t| {
+                    t.format(TIME_FMT).to_string()
+                });
+                format!("{} {time}  {entry}", date.format("%a %Y-%m-%d"))
+            })
+            .collect();
+        use Constraint::*;
+        let [input, summary_row, _, list] = self.body().split(
+            Direction::Vertical,
+            [Length(1), Length(1), Length(1), Fill],
+        );
+        let summary = match matches.len() {
+            0 => "no matching entries".to_owned(),
+            1 => "1 matching entry".to_owned(),
+            n => format!("{n} matching entries"),
+        };
+        self.draw_lines(summary_row, &[summary])?;
+        self.draw_list(list, &matches, Some(self.history_cursor))?;
+
+        let label = format!("Search log: {}", self.history);
+        self.draw_lines(input, &[label])?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    fn history_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let n = self.history_matches().len();
+        let up = event.code == KeyCode::Up;
+        match self.action(event) {
+            Some(Action::Help) => return self.help(),
+            Some(Action::SearchMode) => {
+                self.search_mode = self.search_mode.next();
+                self.history_cursor = 0;
+            }
+            Some(Action::Select) if up && self.history_cursor > 0 => {
+                self.history_cursor -= 1;
+            }
+            Some(Action::Select) if !up && self.history_cursor + 1 < n => {
+                self.history_cursor += 1;
+            }
+            Some(Action::Accept) if n > 0 => {
+                let (date, i, _) = self.history_matches()[self.history_cursor];
+                self.date = date;
+                self.selected = i;
+                return self.render_main();
+            }
+            Some(Action::Back) => return self.back(),
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.history.push(c);
+                    self.history_cursor = 0;
+                }
+                KeyCode::Backspace => {
+                    self.history.pop();
+                    self.history_cursor = 0;
+                }
+                _ => return Ok(()),
+            },
+        }
+        self.render_history()
+    }
+
+    fn food_compare(&mut self) -> io::Result<()> {
+        self.food_compare = Default::default();
+        self.food_compare_cursor = [0; 2];
+        self.food_compare_pane = 0;
+        self.render_food_compare()
+    }
+
+    /// the highlighted food in each pane of the FoodCompare screen
+    fn compared_foods(&self) -> [Option<&Food>; 2] {
+        [0, 1].map(|pane| {
+            self.matching_foods(&self.food_compare[pane], CANDIDATES)
+                .get(self.food_compare_cursor[pane])
+                .copied()
+        })
+    }
+
+    /// draw a pane of the FoodCompare screen starting at `x`: the search, its
+    /// matches, and the nutrition of the highlighted food in `amounts` of it,
+    /// marking the values that differ from `other` by more than a tenth
+    fn draw_food_pane(
+        &mut self,
+        area: Rect,
+        pane: usize,
+        amounts: [f64; 2],
+    ) -> io::Result<()> {
+        use Constraint::*;
+        let [input, _, list, _, title, _, rest] = area.split(
+            Direction::Vertical,
+            [
+                Length(1),
+                Length(1),
+                Length(CANDIDATES as u16),
+                Length(1),
+                Length(1),
+                Length(1),
+                Fill,
+            ],
+        );
+        let query = &self.food_compare[pane];
+        let label = format!("Food: {query}");
+        let lines: Vec<(String, Vec<usize>)> = self
+            .matching_foods(query, CANDIDATES)
+            .iter()
+            .enumerate()
+            .map(|(i, food)| {
+                let cursor = self.food_compare_cursor[pane];
+                let arrow = if i == cursor { '>' } else { ' ' };
+                (
+                    format!("{arrow} {} ({})", food.name, food.unit),
+                    self.matched(query, &food.name, 2),
+                )
+            })
+            .collect();
+        self.draw_lines(input, &[label])?;
+        self.draw_matched(list, &lines)?;
+
+        let foods = self.compared_foods().map(|food| food.cloned());
+        let [Some(food), Some(other)] = [pane, 1 - pane].map(|i| &foods[i])
+        else {
+            return Ok(());
+        };
+        let (n, m) = (amounts[pane], amounts[1 - pane]);
+        let mut values = vec![
+            (
+                "Calories",
+                Some(n * food.calories),
+                Some(m * other.calories),
+            ),
+            ("Protein", Some(n * food.protein), Some(m * other.protein)),
+            ("Carbs", Some(n * food.carbs), Some(m * other.carbs)),
+            ("Fat", Some(n * food.fat), Some(m * other.fat)),
+        ];
+        for (i, name) in NUTRIENTS.iter().enumerate() {
+            let v = food.nutrients[i].map(|v| n * v);
+            values.push((name, v, other.nutrients[i].map(|v| m * v)));
+        }
+        let amount = match food.grams() {
+            Some(g) if other.grams().is_some() => format!("{:.0} g", n * g),
+            _ => format!("{n} {}", food.unit),
+        };
+        self.draw_title(title, &format!("{} per {amount}", food.name))?;
+        for (i, (name, v, o)) in values.into_iter().enumerate() {
+            let Some(v) = v else { continue };
+            if i >= rest.height as usize {
+                break;
+            }
+            self.move_to(rest.x, rest.y + i as u16)?;
+            let line = match o {
+                Some(o) => format!("{name:>8}: {v:>6.1} ({:+.1})", v - o),
+                None => format!("{name:>8}: {v:>6.1}"),
+            };
+            if o.is_some_and(|o| (v - o).abs() > 0.1 * v.max(o)) {
+                self.write_colored(&line, self.config.theme.changed)?;
+            } else {
+                self.write_str(&line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// draw two foods side by side, per 100 g if both are measured by weight
+    /// and per unit otherwise
+    fn render_food_compare(&mut self) -> io::Result<()> {
+        self.set_state(State::FoodCompare);
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::FoodCompare)?;
+        let amounts = match self.compared_foods().map(|f| f?.grams()) {
+            [Some(a), Some(b)] => [100.0 / a, 100.0 / b],
+            _ => [1.0; 2],
+        };
+        let panes = self.columns();
+        self.draw_food_pane(panes[0], 0, amounts)?;
+        self.draw_food_pane(panes[1], 1, amounts)?;
+
+        // leave the cursor after the search in the current pane
+        let area = panes[self.food_compare_pane];
+        let label =
+            format!("Food: {}", self.food_compare[self.food_compare_pane]);
+        self.move_to(area.x + label.width() as u16, area.y)?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    fn food_compare_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let pane = self.food_compare_pane;
+        let n = self
+            .matching_foods(&self.food_compare[pane], CANDIDATES)
+            .len();
+        let up = event.code == KeyCode::Up;
+        let action = self.action(event);
+        let cursor = &mut self.food_compare_cursor[pane];
+        match action {
+            Some(Action::Help) => return self.help(),
+            Some(Action::SearchMode) => {
+                self.search_mode = self.search_mode.next();
+                self.food_compare_cursor = [0; 2];
+            }
+            Some(Action::Select) if up && *cursor > 0 => *cursor -= 1,
+            Some(Action::Select) if !up && *cursor + 1 < n => *cursor += 1,
+            Some(Action::Pane) => {
+                self.food_compare_pane = 1 - pane;
+            }
+            Some(Action::Back) => return self.back(),
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.food_compare[pane].push(c);
+                    *cursor = 0;
+                }
+                KeyCode::Backspace => {
+                    self.food_compare[pane].pop();
+                    *cursor = 0;
+                }
+                _ => return Ok(()),
+            },
+        }
+        self.render_food_compare()
+    }
+
+    fn search(&mut self) -> io::Result<()> {
+        self.search.clear();
+        self.search_cursor = 0;
+        self.render_search()
+    }
+
+    /// the parts of the Search screen: the input, the list of matches, and
+    /// the details of the highlighted one
+    fn search_layout(&self) -> [Rect; 3] {
+        use Constraint::*;
+        let [input, _, list, _, details] = self.body().split(
+            Direction::Vertical,
+            [Length(1), Length(1), Fill, Length(1), Length(2)],
+        );
+        [input, list, details]
+    }
+
+    /// the number of matches that fit on the Search screen
+    fn search_height(&self) -> usize {
+        self.search_layout()[1].height as usize
+    }
+
+    /// draw the search input and the foods matching it, for logging one with
+    /// nothing more than its name and a quantity
+    fn render_search(&mut self) -> io::Result<()> {
+        self.set_state(State::Search);
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Search)?;
+
+        let [input, list, details_area] = self.search_layout();
+        let matches = self.matching_foods(&self.search, list.height as usize);
+        let lines: Vec<(String, Vec<usize>)> = matches
+            .iter()
+            .enumerate()
+            .map(|(i, food)| {
+                let arrow = if i == self.search_cursor { '>' } else { ' ' };
+                let line = format!(
+                    "{arrow} {} ({}, {:.0} kcal)",
+                    food.name, food.unit, food.calories
+                );
+                (line, self.matched(&self.search, &food.name, 2))
+            })
+            .collect();
+        let details = matches
+            .get(self.search_cursor)
+            .map_or(Vec::new(), |food| food.details());
+        self.draw_matched(list, &lines)?;
+        self.draw_lines(details_area, &details)?;
+
+        let label = format!("Log: {}", self.search);
+        self.draw_lines(input, &[label])?;
+        self.queue(cursor::Show)?;
+        self.flush()
+    }
+
+    fn search_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let matches = self.matching_foods(&self.search, self.search_height());
+        let n = matches.len();
+        let food = matches.get(self.search_cursor).copied().cloned();
+        let up = event.code == KeyCode::Up;
+        match self.action(event) {
+            Some(Action::Help) => return self.help(),
+            Some(Action::SearchMode) => {
+                self.search_mode = self.search_mode.next();
+                self.search_cursor = 0;
+            }
+            Some(Action::Select) if up && self.search_cursor > 0 => {
+                self.search_cursor -= 1;
+            }
+            Some(Action::Select) if !up && self.search_cursor + 1 < n => {
+                self.search_cursor += 1;
+            }
+            Some(Action::Accept) => {
+                return match food {
+                    Some(food) => self.prompt_quantity(food),
+                    None => Ok(()),
+                };
+            }
+            Some(Action::Back) => return self.back(),
+            _ => match event.code {
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.search_cursor = 0;
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.search_cursor = 0;
+                }
+                _ => return Ok(()),
+            },
+        }
+        self.render_search()
+    }
+
+    fn review_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let n = self.review.len();
+        let up = event.code == KeyCode::Up;
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Select) if up && self.review_cursor > 0 => {
+                self.review_cursor -= 1;
+                self.render_review()?;
+            }
+            Some(Action::Select) if !up && self.review_cursor + 1 < n => {
+                self.review_cursor += 1;
+                self.render_review()?;
+            }
+            Some(Action::Accept) if n > 0 => {
+                match self.review[self.review_cursor] {
+                    ReviewItem::Capture(i) => {
+                        self.inbox_cursor = i;
+                        self.render_inbox()?;
+                    }
+                    ReviewItem::Entry(date, i, _) => {
+                        self.date = date;
+                        self.selected = i;
+                        self.render_main()?;
+                    }
+                }
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn inbox_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let n = self.inbox.items.len();
+        let up = event.code == KeyCode::Up;
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Select) if up && self.inbox_cursor > 0 => {
+                self.inbox_cursor -= 1;
+                self.render_inbox()?;
+            }
+            Some(Action::Select) if !up && self.inbox_cursor + 1 < n => {
+                self.inbox_cursor += 1;
+                self.render_inbox()?;
+            }
+            Some(Action::Accept) if n > 0 => {
+                // resolve the capture by editing its text on the QuickAdd
+                // screen
+                self.resolving = Some(self.inbox_cursor);
+                self.set_state(State::QuickAdd);
+                self.quick = self.inbox.items[self.inbox_cursor].text.clone();
+                self.render_quick_add()?;
+            }
+            Some(Action::Delete) if n > 0 => {
+                self.inbox.items.remove(self.inbox_cursor);
+                self.inbox.save()?;
+                self.render_inbox()?;
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// show the Calendar screen for the month of the current day
+    fn calendar(&mut self) -> io::Result<()> {
+        self.calendar = self.date;
+        self.render_calendar()
+    }
+
+    /// the color of `date` on the Calendar screen, by how close its calories
+    /// came to the budget
+    fn day_color(&self, date: NaiveDate) -> Option<Color> {
+        let budget = self.targets.get(date).calories?;
+        if self.log.day(date).is_empty() {
+            return None;
+        }
+        let off = (self.log.totals(date).calories / budget - 1.0).abs();
+        let theme = self.config.theme;
+        Some(if off <= 0.1 {
+            theme.good
+        } else if off <= 0.25 {
+            theme.near
+        } else {
+            theme.over
+        })
+    }
+
+    fn render_calendar(&mut self) -> io::Result<()> {
+        self.set_state(State::Calendar);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Calendar)?;
+
+        // seven columns of four characters each, under the month and the
+        // names of the days, with the legend below
+        let area = self.body().centered(28, 11);
+        let (x, y) = (area.x, area.y);
+        let first = self.calendar.with_day(1).unwrap();
+        self.move_to(x, y)?;
+        self.write_str(&first.format("%B %Y").to_string())?;
+        self.move_to(x, y + 2)?;
+        self.write_str(" Mo  Tu  We  Th  Fr  Sa  Su")?;
+        let offset = first.weekday().num_days_from_monday();
+        let days = first
+            .iter_days()
+            .take_while(|date| date.month() == first.month());
+        for date in days {
+            let i = offset + date.day0();
+            self.move_to(x + 4 * (i % 7) as u16, y + 3 + (i / 7) as u16)?;
+            let cell = if date == self.calendar {
+                format!("[{:>2}]", date.day())
+            } else {
+                format!(" {:>2} ", date.day())
+            };
+            match self.day_color(date) {
+                Some(color) => self.write_colored(&cell, color)?,
+                None => {
+                    self.write_str(&cell)?;
+                }
+            }
+        }
+
+        self.move_to(x, y + 10)?;
+        if self.targets.defaults().calories.is_some() {
+            // a swatch of each color that days can take, with how far off
+            // the budget it means
+            let theme = self.config.theme;
+            let legend = [
+                (theme.good, "within 10%"),
+                (theme.near, "25%"),
+                (theme.over, "more"),
+            ];
+            for (i, (color, text)) in legend.into_iter().enumerate() {
+                if i > 0 {
+                    self.write_str("  ")?;
+                }
+                self.write_colored(&self.border.full.to_string(), color)?;
+                self.write_str(&format!(" {text}"))?;
+            }
+        } else {
+            self.write_str("set `budget` in the config to color days")?;
+        }
+        self.flush()
+    }
+
+    fn calendar_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let date = match (self.action(event), event.code) {
+            (Some(Action::Help), _) => return self.help(),
+            (Some(Action::Select), KeyCode::Left) => {
+                self.calendar.checked_sub_days(Days::new(1))
+            }
+            (Some(Action::Select), KeyCode::Right) => {
+                self.calendar.checked_add_days(Days::new(1))
             }
-            KeyCode::Enter => {
-                if let Ok(FoodQuantity(food, n)) =
-                    FoodQuantity::try_from(&self.buf)
-                {
-                    // TODO also store the food in the database
-                    self.today += food * n;
+            (Some(Action::Select), KeyCode::Up) => {
+                self.calendar.checked_sub_days(Days::new(7))
+            }
+            (Some(Action::Select), _) => {
+                self.calendar.checked_add_days(Days::new(7))
+            }
+            (Some(Action::Month), KeyCode::PageUp) => {
+                self.calendar.checked_sub_months(Months::new(1))
+            }
+            (Some(Action::Month), _) => {
+                self.calendar.checked_add_months(Months::new(1))
+            }
+            (Some(Action::Accept), _) => {
+                self.date = self.calendar;
+                self.selected = 0;
+                return self.render_main();
+            }
+            (Some(Action::Back), _) => return self.back(),
+            _ => None,
+        };
+        if let Some(date) = date {
+            self.calendar = date;
+            self.render_calendar()?;
+        }
+        Ok(())
+    }
+
+    /// draw a read-only view of the current day for follow mode
+    fn render_follow(&mut self) -> io::Result<()> {
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_help(&["q Quit"])?;
+        self.draw_today()
+    }
+
+    /// show today's log read-only, reloading it whenever another instance
+    /// writes to it, until q or Esc is pressed
+    fn follow(&mut self) -> io::Result<()> {
+        self.following = true;
+        let mut modified = self.log.modified();
+        self.render_follow()?;
+        loop {
+            if poll(Duration::from_secs(1))? {
+                match read()? {
+                    Event::Key(event)
+                        if matches!(
+                            event.code,
+                            KeyCode::Char('q') | KeyCode::Esc
+                        ) =>
+                    {
+                        return Ok(());
+                    }
+                    Event::Resize(width, height) => {
+                        self.resize(width, height);
+                        self.render_follow()?;
+                    }
+                    _ => {}
                 }
-                self.render_main()?;
             }
-            KeyCode::Esc => {
-                // TODO other clean up? at least need to clean buf
+            let today = Local::now().date_naive();
+            if self.log.modified() != modified || self.date != today {
+                modified = self.log.modified();
+                self.date = today;
+                self.log.reload()?;
+                self.usage = self.log.usage();
+                self.render_follow()?;
+            }
+        }
+    }
+
+    /// switch to the screen for `state`, remembering the current one to go
+    /// back to. switching to a screen already behind this one goes back to it
+    /// instead, and prompts and the help are passed over, since they close
+    /// themselves
+    fn set_state(&mut self, state: State) {
+        if let Some(i) = self.screens.iter().position(|&s| s == state) {
+            self.screens.truncate(i);
+        } else if state != self.state
+            && !self.state.is_prompt()
+            && !self.state.is_help()
+        {
+            self.screens.push(self.state);
+        }
+        self.state = state;
+    }
+
+    /// return to the screen the current one was opened from, or the main
+    /// screen
+    fn back(&mut self) -> io::Result<()> {
+        self.state = self.screens.pop().unwrap_or(State::Main);
+        self.render()
+    }
+
+    /// redraw the screen for the current state
+    fn render(&mut self) -> io::Result<()> {
+        match self.state {
+            State::Main => self.render_main(),
+            State::AddFood | State::SplitDish => self.render_form(),
+            State::CopyDay => self.render_copy_day(),
+            State::QuickAdd => self.render_quick_add(),
+            State::Reports => self.render_reports(),
+            State::Report => self.render_report(),
+            State::Prompt => self.render_prompt(),
+            State::Inbox => self.render_inbox(),
+            State::Review => self.render_review(),
+            State::Calendar => self.render_calendar(),
+            State::Weigh => self.render_weigh(),
+            State::Numpad => self.render_numpad(),
+            State::Compare => self.render_compare(),
+            State::History => self.render_history(),
+            State::FoodCompare => self.render_food_compare(),
+            State::Search => self.render_search(),
+            State::Help => self.render_help(),
+        }
+    }
+
+    /// blank the screen until a key is pressed or, if a passphrase is
+    /// configured, until it is typed followed by Enter
+    fn lock(&mut self) -> io::Result<()> {
+        let passphrase = self.config.lock_passphrase.clone();
+        let mut typed = String::new();
+        loop {
+            self.execute(cursor::Hide)?;
+            self.execute(Clear(ClearType::All))?;
+            let msg = match passphrase {
+                Some(_) => "Locked. Type the passphrase and press Enter",
+                None => "Locked. Press any key to resume",
+            };
+            let (x, y) = self.center();
+            self.move_to(x.saturating_sub(msg.len() as u16 / 2), y)?;
+            self.write_str(msg)?;
+            self.flush()?;
+
+            let event = match read()? {
+                Event::Key(event) => event,
+                Event::Resize(width, height) => {
+                    self.resize(width, height);
+                    continue;
+                }
+                _ => continue,
+            };
+            let Some(passphrase) = &passphrase else {
+                break;
+            };
+            match event.code {
+                KeyCode::Char(c) => typed.push(c),
+                KeyCode::Backspace => {
+                    typed.pop();
+                }
+                KeyCode::Enter if typed == *passphrase => break,
+                KeyCode::Enter => typed.clear(),
+                _ => {}
+            }
+        }
+        self.render()
+    }
+
+    fn render_reports(&mut self) -> io::Result<()> {
+        self.set_state(State::Reports);
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Reports)?;
+
+        let [title, list] = self.title_layout();
+        self.draw_title(title, "Reports")?;
+        // the built-in reports followed by any custom ones
+        let names: Vec<String> = ReportKind::ALL
+            .iter()
+            .map(|kind| kind.name().to_owned())
+            .chain(self.config.reports.iter().map(|spec| spec.name.clone()))
+            .collect();
+        self.draw_list(list, &names, Some(self.reports_cursor))?;
+        self.flush()
+    }
+
+    fn reports_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let n = ReportKind::ALL.len() + self.config.reports.len();
+        let up = event.code == KeyCode::Up;
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Select) if up && self.reports_cursor > 0 => {
+                self.reports_cursor -= 1;
+                self.render_reports()?;
+            }
+            Some(Action::Select) if !up && self.reports_cursor + 1 < n => {
+                self.reports_cursor += 1;
+                self.render_reports()?;
+            }
+            Some(Action::Accept) => {
+                let kind = match ReportKind::ALL.get(self.reports_cursor) {
+                    Some(kind) => *kind,
+                    None => ReportKind::Custom(
+                        self.reports_cursor - ReportKind::ALL.len(),
+                    ),
+                };
+                self.open_report(kind)?;
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// show the report of `kind` on the Report screen, over the default period
+    fn open_report(&mut self, kind: ReportKind) -> io::Result<()> {
+        self.set_state(State::Report);
+        self.report = Report {
+            kind,
+            period: 2,
+            month: self.date.with_day(1).unwrap(),
+            year: self.date.year(),
+            ..Report::default()
+        };
+        self.refresh_report()
+    }
+
+    /// regenerate the current report's contents for its period
+    fn refresh_report(&mut self) -> io::Result<()> {
+        let kind = self.report.kind;
+        let to = self.date;
+        let from = to - Days::new(PERIODS[self.report.period] - 1);
+        let lines = match kind {
+            ReportKind::Completeness => report::completeness(&self.foods),
+            ReportKind::TimeOfDay => report::time_of_day(&self.log, from, to),
+            ReportKind::Weekdays => {
+                report::weekdays(&self.log, from, to, &self.targets)
+            }
+            ReportKind::Calories => report::calories(
+                &self.log,
+                &self.events,
+                from,
+                to,
+                &self.targets,
+            ),
+            ReportKind::Trends => {
+                let width = self.cols.saturating_sub(4) as usize;
+                report::trends(&self.log, from, to, width)
+            }
+            ReportKind::Monthly => {
+                report::monthly(&self.log, self.report.month, &self.targets)
+            }
+            ReportKind::Split => report::split(&self.log, from, to),
+            ReportKind::TopFoods => report::top_foods(&self.log, from, to),
+            ReportKind::Yearly => report::yearly(&self.log, self.report.year),
+            ReportKind::Day => report::day(
+                &self.log,
+                self.date,
+                &self.targets,
+                self.config.assess,
+            ),
+            ReportKind::Custom(i) => {
+                report::custom(&self.log, &self.config.reports[i], self.date)
+            }
+        };
+        self.report.title = if kind.by_month() {
+            format!("{}, {}", kind.name(), self.report.month.format("%B %Y"))
+        } else if kind.by_year() {
+            format!("{}, {}", kind.name(), self.report.year)
+        } else if let ReportKind::Day = kind {
+            format!("{}, {}", kind.name(), self.date.format("%a %Y-%m-%d"))
+        } else if let ReportKind::Custom(i) = kind {
+            self.config.reports[i].name.clone()
+        } else if kind.uses_period() {
+            format!(
+                "{}, {} to {}",
+                kind.name(),
+                from.format(DATE_FMT),
+                to.format(DATE_FMT)
+            )
+        } else {
+            kind.name().to_owned()
+        };
+        self.report.lines = lines;
+        self.report.scroll = 0;
+        self.render_report()
+    }
+
+    /// the number of report lines that fit inside the boundary
+    fn report_height(&self) -> usize {
+        self.title_layout()[1].height as usize
+    }
+
+    fn render_report(&mut self) -> io::Result<()> {
+        self.execute(cursor::Hide)?;
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::Report)?;
+
+        let [title, body] = self.title_layout();
+        let heading = self.report.title.clone();
+        self.draw_title(title, &heading)?;
+        let lines = self.report.lines[self.report.scroll..].to_vec();
+        self.draw_lines(body, &lines)?;
+        self.flush()?;
+        Ok(())
+    }
+
+    fn report_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let max = self.report.lines.len().saturating_sub(self.report_height());
+        let back = matches!(event.code, KeyCode::Up | KeyCode::Left);
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Select) if back && self.report.scroll > 0 => {
+                self.report.scroll -= 1;
+                self.render_report()?;
+            }
+            Some(Action::Select) if !back && self.report.scroll < max => {
+                self.report.scroll += 1;
+                self.render_report()?;
+            }
+            Some(Action::Period) if back && self.report.period > 0 => {
+                self.report.period -= 1;
+                self.refresh_report()?;
+            }
+            Some(Action::Period)
+                if !back && self.report.period + 1 < PERIODS.len() =>
+            {
+                self.report.period += 1;
+                self.refresh_report()?;
+            }
+            Some(Action::Month) => {
+                let month = Months::new(1);
+                let date = if back {
+                    self.report.month.checked_sub_months(month)
+                } else {
+                    self.report.month.checked_add_months(month)
+                };
+                if let Some(date) = date {
+                    self.report.month = date;
+                    self.refresh_report()?;
+                }
+            }
+            Some(Action::Year) => {
+                self.report.year += if back { -1 } else { 1 };
+                self.refresh_report()?;
+            }
+            Some(Action::Back) => {
+                self.back()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// start copying entries from the day before the current one
+    fn copy_day(&mut self) -> io::Result<()> {
+        self.set_state(State::CopyDay);
+        let from = self.date - Days::new(1);
+        self.copy_from(from)
+    }
+
+    /// switch the copy screen to `from`, selecting all of its entries
+    fn copy_from(&mut self, from: NaiveDate) -> io::Result<()> {
+        self.copy = CopyDay {
+            from,
+            selected: vec![true; self.log.day(from).len()],
+            cursor: 0,
+        };
+        self.render_copy_day()
+    }
+
+    fn render_copy_day(&mut self) -> io::Result<()> {
+        self.execute(Clear(ClearType::All))?;
+        self.draw_boundary()?;
+        self.draw_keys(State::CopyDay)?;
+
+        let header = format!(
+            "Copy from {} to {}",
+            self.copy.from.format(DATE_FMT),
+            self.date.format(DATE_FMT)
+        );
+        let [title, list] = self.title_layout();
+        self.draw_title(title, &header)?;
+
+        let entries: Vec<String> = self
+            .log
+            .day(self.copy.from)
+            .iter()
+            .zip(&self.copy.selected)
+            .map(|(entry, &selected)| {
+                let mark = if selected { 'x' } else { ' ' };
+                format!("[{mark}] {entry}")
+            })
+            .collect();
+        if entries.is_empty() {
+            self.draw_lines(list, &["no entries".to_owned()])?;
+        }
+        self.draw_list(list, &entries, Some(self.copy.cursor))?;
+
+        self.flush()?;
+        Ok(())
+    }
+
+    fn copy_day_form(
+        &mut self,
+        event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let n = self.copy.selected.len();
+        let back = matches!(event.code, KeyCode::Up | KeyCode::Left);
+        match self.action(event) {
+            Some(Action::Help) => self.help()?,
+            Some(Action::Day) if back => {
+                self.copy_from(self.copy.from - Days::new(1))?;
+            }
+            Some(Action::Day) => {
+                self.copy_from(self.copy.from + Days::new(1))?;
+            }
+            Some(Action::Select) if back && self.copy.cursor > 0 => {
+                self.copy.cursor -= 1;
+                self.render_copy_day()?;
+            }
+            Some(Action::Select) if !back && self.copy.cursor + 1 < n => {
+                self.copy.cursor += 1;
+                self.render_copy_day()?;
+            }
+            Some(Action::Toggle) if n > 0 => {
+                let c = self.copy.cursor;
+                self.copy.selected[c] = !self.copy.selected[c];
+                self.render_copy_day()?;
+            }
+            Some(Action::Accept) => {
+                let entries = self.log.day(self.copy.from).to_vec();
+                let selected = std::mem::take(&mut self.copy.selected);
+                let entries = entries
+                    .into_iter()
+                    .zip(selected)
+                    .filter_map(|(entry, selected)| selected.then_some(entry))
+                    .collect();
+                self.add_entries(entries);
+                self.save_log()?;
                 self.render_main()?;
             }
+            Some(Action::Back) => {
+                self.back()?;
+            }
             _ => {}
         }
         Ok(())
     }
 }
 
+/// the files holding the log and everything else personal to the user
+const PERSONAL_FILES: [&str; 7] = [
+    "entries",
+    "entries.journal",
+    "entries.tmp",
+    "targets",
+    "inbox",
+    "events",
+    "config",
+];
+
+/// the files making up the food database
+const FOOD_FILES: [&str; 2] = ["foods", "tags"];
+
+/// the phrase that must be typed to confirm `macroni reset`
+const RESET_PHRASE: &str = "delete my data";
+
+/// delete the file at `path`, returning false if it did not exist
+fn delete(path: &str) -> io::Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// delete the [PERSONAL_FILES] and, unless `--keep-foods` is given, the
+/// [FOOD_FILES] from the current directory, for handing over a shared machine.
+/// the files are only unlinked, so their contents may still be recoverable
+/// from the disk, and exports or other copies made elsewhere are left alone.
+/// this requires `--confirm` and then typing [RESET_PHRASE]
+fn reset(args: &[String]) -> io::Result<()> {
+    let keep_foods = args.iter().any(|arg| arg == "--keep-foods");
+    let known = |arg: &String| arg == "--confirm" || arg == "--keep-foods";
+    if !args.iter().any(|arg| arg == "--confirm") || !args.iter().all(known) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "usage: macroni reset --confirm [--keep-foods]",
+        ));
+    }
+    let mut files = PERSONAL_FILES.to_vec();
+    if !keep_foods {
+        files.extend(FOOD_FILES);
+    }
+    files.retain(|file| Path::new(file).exists());
+    if files.is_empty() {
+        println!("nothing to delete");
+        return Ok(());
+    }
+    println!("this deletes {}", files.join(", "));
+    println!("exports and other copies of your data are not touched");
+    print!("type `{RESET_PHRASE}` to continue: ");
+    stdout().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    if answer.trim() != RESET_PHRASE {
+        println!("nothing deleted");
+        return Ok(());
+    }
+    for file in files {
+        if delete(file)? {
+            println!("deleted {file}");
+        }
+    }
+    Ok(())
+}
+
+/// append `text` to the inbox, stamped with the current time
+fn capture(text: &str) -> io::Result<()> {
+    let mut inbox = Inbox::load("inbox")?;
+    inbox.items.push(Capture {
+        stamp: Local::now().naive_local(),
+        text: text.replace(['\t', '\n'], " "),
+    });
+    inbox.save()
+}
+
+/// write the log to Arrow IPC files in `dir`, or the current directory
+#[cfg(feature = "arrow")]
+fn export_arrow(dir: Option<&String>) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let dir = std::path::Path::new(dir.map_or(".", String::as_str));
+    let days = match log.span() {
+        Some((first, last)) => model::days(&log, first, last),
+        None => Vec::new(),
+    };
+    export::arrow(&days, dir).map_err(|e| io::Error::other(e.to_string()))
+}
+
+#[cfg(not(feature = "arrow"))]
+fn export_arrow(_dir: Option<&String>) -> io::Result<()> {
+    Err(io::Error::other("built without the `arrow` feature"))
+}
+
+/// read the next weight reported by the scale at `path`
+#[cfg(feature = "scale")]
+fn read_weight(path: &str) -> io::Result<f64> {
+    scale::read(path)
+}
+
+#[cfg(not(feature = "scale"))]
+fn read_weight(_path: &str) -> io::Result<f64> {
+    Err(io::Error::other("built without the `scale` feature"))
+}
+
+/// print the log from `args[0]` to `args[1]`, or all of it, as versioned JSON
+fn export_json(args: &[String]) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let config = Config::load("config")?;
+    let Some((from, to)) = date_range(args, &log)? else {
+        return Ok(());
+    };
+    let doc = model::Document::new(&log, &config, from, to);
+    serde_json::to_writer_pretty(stdout(), &doc)?;
+    println!();
+    Ok(())
+}
+
+/// print a Markdown report of the log from `args[0]` to `args[1]`, or all of it
+fn export_markdown(args: &[String]) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let Some((from, to)) = date_range(args, &log)? else {
+        return Ok(());
+    };
+    for line in report::markdown(&model::days(&log, from, to), from, to) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// print one csv row per entry from `args[0]` to `args[1]`, or in the whole log
+fn export_csv(args: &[String]) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let Some((from, to)) = date_range(args, &log)? else {
+        return Ok(());
+    };
+    for line in report::csv(&model::days(&log, from, to)) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// the range of dates given by the optional `from` and `to` command line
+/// arguments in `args`, defaulting to the first and last dates in `log`. this
+/// is None for an empty log
+fn date_range(
+    args: &[String],
+    log: &Log,
+) -> io::Result<Option<(NaiveDate, NaiveDate)>> {
+    let Some((first, last)) = log.span() else {
+        return Ok(None);
+    };
+    let date = |i: usize, default| match args.get(i) {
+        Some(s) => NaiveDate::parse_from_str(s, DATE_FMT)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)),
+        None => Ok(default),
+    };
+    Ok(Some((date(0, first)?, date(1, last)?)))
+}
+
+/// print the monthly summary for `month`, given as `YYYY-MM`, or for the
+/// current month
+fn report_month(month: Option<&String>) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let config = Config::load("config")?;
+    let first = match month {
+        Some(m) => NaiveDate::parse_from_str(&format!("{m}-01"), DATE_FMT)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+        None => Local::now().date_naive().with_day(1).unwrap(),
+    };
+    println!("Monthly summary, {}", first.format("%B %Y"));
+    let targets = Targets::load("targets", config.goals)?;
+    for line in report::monthly(&log, first, &targets) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// print the year in review for `year`, or for the current year
+fn report_year(year: Option<&String>) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let year = match year {
+        Some(y) => y
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+        None => Local::now().year(),
+    };
+    println!("Year in review, {year}");
+    for line in report::yearly(&log, year) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// print the custom report from the config called `name`
+fn custom_report(name: &str) -> io::Result<()> {
+    let log = Log::load("entries")?;
+    let config = Config::load("config")?;
+    let Some(spec) = config.reports.iter().find(|spec| spec.name == name)
+    else {
+        let names: Vec<&str> = config
+            .reports
+            .iter()
+            .map(|spec| spec.name.as_str())
+            .collect();
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no report `{name}`, try one of: {}", names.join(", ")),
+        ));
+    };
+    println!("{}", spec.name);
+    for line in report::custom(&log, spec, Local::now().date_naive()) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// compare the foods and entries files in the directories `args[0]` and
+/// `args[1]`, printing the differences as JSON if `--json` is also given
+fn diff(args: &[String]) -> io::Result<()> {
+    let json = args.iter().any(|arg| arg == "--json");
+    let dirs: Vec<&Path> = args
+        .iter()
+        .filter(|arg| *arg != "--json")
+        .map(Path::new)
+        .collect();
+    let [old, new] = dirs[..] else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "usage: macroni diff OLD_DIR NEW_DIR [--json]",
+        ));
+    };
+    let load = |dir: &Path| -> io::Result<(Vec<Food>, Log)> {
+        let foods = std::fs::read_to_string(dir.join("foods"))?;
+        Ok((parse_foods(&foods), Log::load(dir.join("entries"))?))
+    };
+    let (old_foods, old_log) = load(old)?;
+    let (new_foods, new_log) = load(new)?;
+    let diff = diff::Diff::new(&old_foods, &old_log, &new_foods, &new_log);
+    if json {
+        serde_json::to_writer_pretty(stdout(), &diff)?;
+        println!();
+    } else {
+        for line in diff.lines() {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+/// bring the logged entries from `--from` to `--to`, or in the whole log, up to
+/// date with `foods`, as after fixing a food's macros or changing its unit.
+/// the quantities of entries in another unit are converted when possible, and
+/// the entries that cannot be are listed and left alone
+fn recalc(args: &[String], foods: &[Food]) -> io::Result<()> {
+    let mut log = Log::load("entries")?;
+    let Some((first, last)) = log.span() else {
+        return Ok(());
+    };
+    let bad = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "usage: macroni recalc [--from DATE] [--to DATE]",
+        )
+    };
+    let (mut from, mut to) = (first, last);
+    for pair in args.chunks(2) {
+        let [flag, value] = pair else {
+            return Err(bad());
+        };
+        let date = NaiveDate::parse_from_str(value, DATE_FMT)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        match flag.as_str() {
+            "--from" => from = date,
+            "--to" => to = date,
+            _ => return Err(bad()),
+        }
+    }
+
+    let dates: Vec<NaiveDate> = log.range(from, to).map(|(d, _)| d).collect();
+    let mut changed = Vec::new();
+    let mut skipped = Vec::new();
+    for (i, &date) in dates.iter().enumerate() {
+        eprint!("\rrecalculating day {} of {}", i + 1, dates.len());
+        let before = log.totals(date).calories;
+        let mut updated = 0;
+        for j in 0..log.day(date).len() {
+            let entry = log.get_mut(date, j).unwrap();
+            let Some(food) = foods.iter().find(|f| f.name == entry.food.name)
+            else {
+                continue;
+            };
+            if food.to_string() == entry.food.to_string() {
+                continue;
+            }
+            let quantity = if food.unit == entry.food.unit {
+                Some(entry.quantity)
+            } else {
+                units::convert(entry.quantity, &entry.food.unit, &food.unit)
+            };
+            let Some(quantity) = quantity else {
+                skipped.push(format!(
+                    "{date}: cannot convert {} from {} to {}",
+                    food.name, entry.food.unit, food.unit
+                ));
+                continue;
+            };
+            entry.food = food.clone();
+            entry.quantity = quantity;
+            updated += 1;
+        }
+        if updated > 0 {
+            let after = log.totals(date).calories;
+            changed.push(format!(
+                "{date}: {updated} entr{}, {before:.0} -> {after:.0} kcal",
+                if updated == 1 { "y" } else { "ies" }
+            ));
+        }
+    }
+    if !dates.is_empty() {
+        eprintln!();
+    }
+    if !changed.is_empty() {
+        log.save()?;
+    }
+    for line in changed.iter().chain(&skipped) {
+        println!("{line}");
+    }
+    println!("{} of {} days changed", changed.len(), dates.len());
+    Ok(())
+}
+
+/// the exit code of `macroni add` for invalid arguments
+const EXIT_USAGE: i32 = 2;
+/// the exit code of `macroni add` when an entry does not match any food
+const EXIT_NO_MATCH: i32 = 3;
+/// the exit code of `macroni add` when the data files cannot be read or written
+const EXIT_IO: i32 = 4;
+
+/// a single entry given to `macroni add` entirely by flags, like
+/// `--food oats --quantity 40 --unit g --date 2024-01-31 --time 08:00 --note x`
+struct AddFlags {
+    food: String,
+    quantity: f64,
+    unit: Option<String>,
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    note: Option<String>,
+}
+
+impl TryFrom<&[String]> for AddFlags {
+    type Error = String;
+
+    fn try_from(args: &[String]) -> Result<Self, Self::Error> {
+        let mut ret = Self {
+            food: String::new(),
+            quantity: 1.0,
+            unit: None,
+            date: None,
+            time: None,
+            note: None,
+        };
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{flag}`"))?;
+            let bad = || format!("bad value `{value}` for `{flag}`");
+            match flag.as_str() {
+                "--food" => ret.food = value.clone(),
+                "--quantity" => {
+                    ret.quantity = expr::eval(value).map_err(|_| bad())?;
+                    if ret.quantity <= 0.0 {
+                        return Err(format!(
+                            "`{flag}` must be more than 0, got `{value}`"
+                        ));
+                    }
+                }
+                "--unit" => ret.unit = Some(value.clone()),
+                "--date" => {
+                    let date = NaiveDate::parse_from_str(value, DATE_FMT);
+                    ret.date = Some(date.map_err(|_| bad())?);
+                }
+                "--time" => {
+                    let time = NaiveTime::parse_from_str(value, TIME_FMT);
+                    ret.time = Some(time.map_err(|_| bad())?);
+                }
+                "--note" => ret.note = Some(value.clone()),
+                _ => return Err(format!("unknown flag `{flag}`")),
+            }
+        }
+        if ret.food.is_empty() {
+            return Err("missing `--food`".to_owned());
+        }
+        Ok(ret)
+    }
+}
+
+/// log the entries described by `args`, like `2 eggs, 40g oats`, at the current
+/// time, those described on each line of stdin if `args` is just `-`, or the
+/// single entry given by [AddFlags]. nothing is logged unless every entry
+/// matches a food in `foods`. this prints a one-line confirmation and returns
+/// the exit code, so that scripts and phone shortcuts can tell what happened
+fn add(args: &[String], foods: &[Food]) -> i32 {
+    let now = Local::now();
+    let mut date = now.date_naive();
+    let mut entries = Vec::new();
+    let mut failed = false;
+    if args.first().is_some_and(|arg| arg.starts_with("--")) {
+        let flags = match AddFlags::try_from(args) {
+            Ok(flags) => flags,
+            Err(e) => {
+                eprintln!("macroni: {e}");
+                return EXIT_USAGE;
+            }
+        };
+        let unit = flags.unit.as_deref();
+        match resolve_named(&flags.food, unit, flags.quantity, foods) {
+            Ok(mut entry) => {
+                entry.time = flags.time;
+                entry.note = flags.note;
+                date = flags.date.unwrap_or(date);
+                entries.push(entry);
+            }
+            Err(e) => {
+                eprintln!("{}: {e}", flags.food);
+                failed = true;
+            }
+        }
+    } else {
+        let input = if args == ["-"] {
+            match io::read_to_string(stdin()) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("macroni: {e}");
+                    return EXIT_IO;
+                }
+            }
+        } else {
+            args.join(" ")
+        };
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            for resolved in parse_entries(line, foods) {
+                match resolved.result {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => {
+                        eprintln!("{}: {e}", resolved.input);
+                        failed = true;
+                    }
+                }
+            }
+        }
+    }
+    if failed {
+        eprintln!("macroni: no entries logged");
+        return EXIT_NO_MATCH;
+    }
+    if entries.is_empty() {
+        eprintln!("macroni: nothing to log");
+        return EXIT_USAGE;
+    }
+
+    let mut log = match Log::load("entries") {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("macroni: {e}");
+            return EXIT_IO;
+        }
+    };
+    let n = entries.len();
+    let mut notes: Vec<String> = entries
+        .iter()
+        .filter_map(|e| {
+            Some(format!("{}: {}", e.food.name, e.food.note.as_ref()?))
+        })
+        .collect();
+    notes.dedup();
+    let mut calories = 0.0;
+    for mut entry in entries {
+        // the current time means nothing for an entry logged to another day
+        if date == now.date_naive() {
+            entry.time.get_or_insert(now.time());
+        }
+        calories += entry.macros().calories;
+        log.add(date, entry);
+    }
+    if let Err(e) = log.save() {
+        eprintln!("macroni: {e}");
+        return EXIT_IO;
+    }
+    let total = log.totals(date).calories;
+    println!(
+        "logged {n} entr{}, {calories:.0} kcal; {total:.0} kcal on {}",
+        if n == 1 { "y" } else { "ies" },
+        date.format(DATE_FMT)
+    );
+    for note in notes {
+        println!("note for {note}");
+    }
+    0
+}
+
 fn main() -> io::Result<()> {
-    let path = "foods";
-    let foods = load_foods(path);
+    let mut args: Vec<String> = std::env::args().collect();
+    // read the food database from stdin instead of the foods file, as in
+    // `cat foods.tsv | macroni --stdin-foods`
+    let stdin_foods = args.iter().any(|arg| arg == "--stdin-foods");
+    args.retain(|arg| arg != "--stdin-foods");
+    let foods = || {
+        if stdin_foods {
+            io::read_to_string(stdin()).map(|s| parse_foods(&s))
+        } else {
+            load_foods("foods")
+        }
+        .and_then(|mut foods| {
+            tags::load("tags", &mut foods)?;
+            Ok(foods)
+        })
+    };
+
+    match args.get(1).map(String::as_str) {
+        Some("add") if stdin_foods && args[2..] == ["-"] => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot read both foods and entries from stdin",
+            ));
+        }
+        Some("add") => {
+            let code = match foods() {
+                Ok(foods) => add(&args[2..], &foods),
+                Err(e) => {
+                    eprintln!("macroni: {e}");
+                    EXIT_IO
+                }
+            };
+            std::process::exit(code);
+        }
+        Some("capture") => return capture(&args[2..].join(" ")),
+        Some("compact") => return Log::load("entries")?.compact(),
+        Some("diff") => return diff(&args[2..]),
+        Some("export-arrow") => return export_arrow(args.get(2)),
+        Some("export-csv") => return export_csv(&args[2..]),
+        Some("export-json") => return export_json(&args[2..]),
+        Some("export-markdown") => return export_markdown(&args[2..]),
+        Some("report") => return custom_report(&args[2..].join(" ")),
+        Some("report-month") => return report_month(args.get(2)),
+        Some("report-year") => return report_year(args.get(2)),
+        Some("recalc") => return recalc(&args[2..], &foods()?),
+        Some("reset") => return reset(&args[2..]),
+        _ => {}
+    }
+
+    let foods = foods()?;
+    let log = Log::load("entries")?;
+    let config = Config::load("config")?;
+    let targets = Targets::load("targets", config.goals)?;
+    let inbox = Inbox::load("inbox")?;
+    let events = Events::load("events")?;
 
     let mut stdout = stdout();
-    let mut tui = Tui::new(&mut stdout, foods);
+    let mut tui =
+        Tui::new(&mut stdout, foods, log, config, targets, inbox, events);
 
     tui.execute(cursor::SavePosition)?;
 
+    term::restore_on_panic();
+
+    if let Some("follow") = args.get(1).map(String::as_str) {
+        let _raw = RawMode::enable()?;
+        return tui.follow();
+    }
+
+    let mut problems = tui.config.errors.clone();
+    let invalid = tui.log.invalid().len();
+    if invalid > 0 {
+        problems.push(format!(
+            "{invalid} line(s) of the log could not be read and are kept as \
+             they are"
+        ));
+    }
+    if !problems.is_empty() {
+        tui.set_status(problems.join("; "), true);
+    }
     tui.render_main()?;
 
-    enable_raw_mode()?;
+    let _raw = RawMode::enable()?;
 
-    let mut right = 0; // same as the 2 in x + MAX_WIDTH + 2 in add_food
-    let mut field = 0;
-    loop {
-        match read()? {
-            Event::Key(event) if tui.state.is_add_food() => {
-                tui.food_form(event, &mut right, &mut field)?
+    while !tui.quit {
+        if tui.reading.is_some() && !poll(task::FRAME)? {
+            tui.check_reading()?;
+            continue;
+        }
+        if let Some(left) = tui.status_left() {
+            if !poll(left)? {
+                tui.clear_status()?;
+                continue;
+            }
+        }
+        if let Some(minutes) = tui.config.lock_after.filter(|&m| m > 0) {
+            if !poll(Duration::from_secs(60 * minutes))? {
+                tui.lock()?;
+                continue;
+            }
+        }
+        let mut event = read()?;
+        if let Event::Key(key) = &mut event {
+            tui.clear_status()?;
+            if tui.config.vim && tui.state.is_list() {
+                key.code = keys::vim_arrow(key.code);
+            }
+        }
+        match event {
+            // keys wait until the scale has been read, except to give up on it
+            Event::Key(event)
+                if tui.reading.is_some() && event.code == KeyCode::Esc =>
+            {
+                tui.cancel_reading()?
+            }
+            Event::Key(_) if tui.reading.is_some() => {}
+            Event::Key(event)
+                if tui.state.is_add_food() || tui.state.is_split_dish() =>
+            {
+                tui.food_form(event)?
+            }
+            Event::Key(event) if tui.state.is_copy_day() => {
+                tui.copy_day_form(event)?
             }
-            Event::Key(event) if event.code == KeyCode::Char('q') => break,
-            Event::Key(event) if event.code == KeyCode::Char('a') => {
-                tui.add_food()?;
+            Event::Key(event) if tui.state.is_quick_add() => {
+                tui.quick_add_form(event)?
             }
+            Event::Key(event) if tui.state.is_reports() => {
+                tui.reports_form(event)?
+            }
+            Event::Key(event) if tui.state.is_report() => {
+                tui.report_form(event)?
+            }
+            Event::Key(event) if tui.state.is_prompt() => {
+                tui.prompt_form(event)?
+            }
+            Event::Key(event) if tui.state.is_inbox() => {
+                tui.inbox_form(event)?
+            }
+            Event::Key(event) if tui.state.is_review() => {
+                tui.review_form(event)?
+            }
+            Event::Key(event) if tui.state.is_calendar() => {
+                tui.calendar_form(event)?
+            }
+            Event::Key(event) if tui.state.is_weigh() => {
+                tui.weigh_form(event)?
+            }
+            Event::Key(event) if tui.state.is_numpad() => {
+                tui.numpad_form(event)?
+            }
+            Event::Key(event) if tui.state.is_compare() => {
+                tui.compare_form(event)?
+            }
+            Event::Key(event) if tui.state.is_history() => {
+                tui.history_form(event)?
+            }
+            Event::Key(event) if tui.state.is_food_compare() => {
+                tui.food_compare_form(event)?
+            }
+            Event::Key(event) if tui.state.is_search() => {
+                tui.search_form(event)?
+            }
+            Event::Key(event) if tui.state.is_help() => tui.help_form(event)?,
+            Event::Key(event) => {
+                if let Some(action) = tui.action(event) {
+                    tui.act(action, event.code)?;
+                }
+            }
+            Event::Paste(text) => tui.paste(&text)?,
+            Event::Mouse(event) => tui.mouse(event)?,
             Event::Resize(width, height) => {
                 tui.resize(width, height);
-                // TODO what to render depends on tui.state
-                tui.render_main()?;
+                tui.render()?;
             }
             _ => {}
         }
     }
 
-    disable_raw_mode()?;
-
-    tui.execute(Clear(ClearType::All))?;
-    tui.flush()?;
-    tui.execute(cursor::RestorePosition)?
-        .execute(cursor::Show)?;
-
     Ok(())
 }
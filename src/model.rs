@@ -0,0 +1,207 @@
+//! the versioned data model handed to other programs by every export: JSON,
+//! csv, Markdown, and Arrow. this is kept separate from the internal types so
+//! that they can change freely, and any change here that could break a
+//! consumer must bump [SCHEMA_VERSION]
+
+use std::{collections::BTreeMap, ops::AddAssign};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    log::{self, Log, DATE_FMT, TIME_FMT},
+    report, NUTRIENTS,
+};
+
+/// the version of the schema described by the types in this module
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// a food, with macros and nutrients per `unit`
+#[derive(Serialize, Deserialize)]
+pub struct Food {
+    pub name: String,
+    pub unit: String,
+    pub calories: f64,
+    pub protein: f64,
+    pub carbs: f64,
+    pub fat: f64,
+    /// the optional nutrients known for the food, keyed by lowercase name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub nutrients: BTreeMap<String, f64>,
+}
+
+/// a logged quantity of a food
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    /// the time of day as `HH:MM`, if known
+    pub time: Option<String>,
+    /// the part of the day the entry was eaten in, if its time is known
+    #[serde(default)]
+    pub meal: Option<String>,
+    pub quantity: f64,
+    /// the fraction of `quantity` actually eaten
+    pub consumed: f64,
+    pub estimated: bool,
+    pub food: Food,
+    pub note: Option<String>,
+}
+
+/// calories and macros eaten
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Totals {
+    pub calories: f64,
+    pub protein: f64,
+    pub carbs: f64,
+    pub fat: f64,
+}
+
+/// everything logged on one day
+#[derive(Serialize, Deserialize)]
+pub struct Day {
+    /// the date as `YYYY-MM-DD`
+    pub date: String,
+    pub note: Option<String>,
+    pub entries: Vec<Entry>,
+    pub totals: Totals,
+}
+
+/// the user's default daily targets
+#[derive(Serialize, Deserialize)]
+pub struct Goals {
+    pub calories: Option<f64>,
+    pub protein: Option<f64>,
+    pub carbs: Option<f64>,
+    pub fat: Option<f64>,
+}
+
+/// the top level of a JSON export
+#[derive(Serialize, Deserialize)]
+pub struct Document {
+    pub schema_version: u32,
+    pub goals: Goals,
+    pub days: Vec<Day>,
+}
+
+impl From<&crate::Food> for Food {
+    fn from(food: &crate::Food) -> Self {
+        let nutrients = NUTRIENTS
+            .iter()
+            .zip(food.nutrients)
+            .filter_map(|(name, v)| Some((name.to_lowercase(), v?)))
+            .collect();
+        Self {
+            name: food.name.clone(),
+            unit: food.unit.clone(),
+            calories: food.calories,
+            protein: food.protein,
+            carbs: food.carbs,
+            fat: food.fat,
+            nutrients,
+        }
+    }
+}
+
+impl From<&log::Entry> for Entry {
+    fn from(entry: &log::Entry) -> Self {
+        Self {
+            time: entry.time.map(|t| t.format(TIME_FMT).to_string()),
+            meal: entry.time.map(|t| report::part_of_day(t).to_owned()),
+            quantity: entry.quantity,
+            consumed: entry.consumed,
+            estimated: entry.estimated,
+            food: Food::from(&entry.food),
+            note: entry.note.clone(),
+        }
+    }
+}
+
+impl From<crate::Macros> for Totals {
+    fn from(m: crate::Macros) -> Self {
+        Self {
+            calories: m.calories,
+            protein: m.protein,
+            carbs: m.carbs,
+            fat: m.fat,
+        }
+    }
+}
+
+impl AddAssign for Totals {
+    fn add_assign(&mut self, rhs: Self) {
+        self.calories += rhs.calories;
+        self.protein += rhs.protein;
+        self.carbs += rhs.carbs;
+        self.fat += rhs.fat;
+    }
+}
+
+impl Entry {
+    /// the amount of the food actually eaten, in its unit
+    pub fn eaten(&self) -> f64 {
+        self.quantity * self.consumed
+    }
+
+    /// the calories and macros actually eaten
+    pub fn totals(&self) -> Totals {
+        let eaten = self.eaten();
+        Totals {
+            calories: self.food.calories * eaten,
+            protein: self.food.protein * eaten,
+            carbs: self.food.carbs * eaten,
+            fat: self.food.fat * eaten,
+        }
+    }
+
+    /// the amount of the nutrient `name`, in lower case, actually eaten, or
+    /// None if it is unknown for the food
+    #[cfg(feature = "arrow")]
+    pub fn nutrient(&self, name: &str) -> Option<f64> {
+        Some(self.food.nutrients.get(name)? * self.eaten())
+    }
+}
+
+impl Day {
+    /// the date of the day
+    pub fn naive_date(&self) -> NaiveDate {
+        NaiveDate::parse_from_str(&self.date, DATE_FMT).unwrap()
+    }
+}
+
+/// collect the days from `from` to `to` that have entries or a note
+pub fn days(log: &Log, from: NaiveDate, to: NaiveDate) -> Vec<Day> {
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .filter(|date| {
+            !log.day(*date).is_empty() || log.day_note(*date).is_some()
+        })
+        .map(|date| Day {
+            date: date.format(DATE_FMT).to_string(),
+            note: log.day_note(date).map(str::to_owned),
+            entries: log.day(date).iter().map(Entry::from).collect(),
+            totals: log.totals(date).into(),
+        })
+        .collect()
+}
+
+impl Document {
+    /// collect the days from `from` to `to` that have entries or a note
+    pub fn new(
+        log: &Log,
+        config: &Config,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let days = days(log, from, to);
+        Self {
+            schema_version: SCHEMA_VERSION,
+            goals: Goals {
+                calories: config.goals.calories,
+                protein: config.goals.protein,
+                carbs: config.goals.carbs,
+                fat: config.goals.fat,
+            },
+            days,
+        }
+    }
+}
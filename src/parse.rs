@@ -0,0 +1,204 @@
+//! parsing of free-form entry descriptions like "2 eggs, 40g oats, 1 cup milk"
+
+use crate::{expr, log::Entry, units, Food};
+
+/// the result of resolving one comma-separated item of the input against the
+/// food database
+pub struct Resolved {
+    /// the original text of the item
+    pub input: String,
+    pub result: Result<Entry, String>,
+}
+
+/// normalize a word for comparison: lowercase and without a plural suffix
+fn normalize(word: &str) -> String {
+    let word = word.to_lowercase();
+    if let Some(w) = word.strip_suffix("es").filter(|w| w.ends_with('s')) {
+        return w.to_owned();
+    }
+    match word.strip_suffix('s') {
+        Some(w) if !w.is_empty() && !w.ends_with('s') => w.to_owned(),
+        _ => word,
+    }
+}
+
+/// split a leading quantity off of `token`, returning the quantity and any
+/// unit attached directly to it, as in "40g"
+fn split_quantity(token: &str) -> Option<(f64, Option<&str>)> {
+    if let Ok(q) = expr::eval(token) {
+        return Some((q, None));
+    }
+    let i = token.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (num, unit) = token.split_at(i);
+    Some((num.parse().ok()?, Some(unit)))
+}
+
+/// find the food whose name best matches `words`. every word must be a prefix
+/// of some word in the food's name, and shorter names are preferred
+fn find_food<'a>(words: &[&str], foods: &'a [Food]) -> Option<&'a Food> {
+    if words.is_empty() {
+        return None;
+    }
+    let words: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+    foods
+        .iter()
+        .filter(|food| {
+            let name: Vec<String> =
+                food.name.split_whitespace().map(normalize).collect();
+            words
+                .iter()
+                .all(|w| name.iter().any(|n| n.starts_with(w.as_str())))
+        })
+        .min_by_key(|food| food.name.split_whitespace().count())
+}
+
+fn same_unit(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// `quantity` of `unit` in the unit of `food`, converting between units of
+/// the same kind, like ounces and grams. anything else is a mismatch
+fn in_food_unit(quantity: f64, unit: &str, food: &Food) -> Result<f64, String> {
+    if same_unit(unit, &food.unit) {
+        return Ok(quantity);
+    }
+    units::convert(quantity, unit, &food.unit).ok_or_else(|| {
+        format!(
+            "unit `{unit}` does not match {} ({}) and cannot be converted",
+            food.name, food.unit
+        )
+    })
+}
+
+/// parse a quantity typed for a food measured in `unit`. the number may be
+/// followed by another unit to convert from, as in `16 oz` or `16oz` for a
+/// food measured in grams
+pub fn quantity(input: &str, unit: &str) -> Result<f64, String> {
+    let input = input.trim();
+    if let Ok(q) = expr::eval(input) {
+        return Ok(q);
+    }
+    // take the longest expression before a space as the number, to allow for
+    // units like `fl oz`
+    let split = input
+        .char_indices()
+        .filter(|(_, c)| c.is_whitespace())
+        .rev()
+        .find_map(|(i, _)| Some((expr::eval(&input[..i]).ok()?, &input[i..])))
+        .or_else(|| match split_quantity(input) {
+            Some((q, Some(from))) => Some((q, from)),
+            _ => None,
+        });
+    let Some((q, from)) = split else {
+        return Err(format!("invalid quantity `{input}`"));
+    };
+    let from = from.trim();
+    if same_unit(from, unit) {
+        return Ok(q);
+    }
+    units::convert(q, from, unit).ok_or_else(|| {
+        format!("unit `{from}` does not match `{unit}` and cannot be converted")
+    })
+}
+
+fn resolve(item: &str, foods: &[Food]) -> Result<Entry, String> {
+    let tokens: Vec<&str> = item.split_whitespace().collect();
+    let (quantity, unit, rest) =
+        match tokens.first().and_then(|t| split_quantity(t)) {
+            Some((q, unit)) => (q, unit, &tokens[1..]),
+            None => (1.0, None, &tokens[..]),
+        };
+    if quantity <= 0.0 {
+        return Err(format!("quantity must be more than 0, got {quantity}"));
+    }
+
+    if let Some(unit) = unit {
+        let food = find_food(rest, foods)
+            .ok_or_else(|| format!("no food matching `{}`", rest.join(" ")))?;
+        let quantity = in_food_unit(quantity, unit, food)?;
+        return Ok(Entry::new(food.clone(), quantity));
+    }
+
+    // try treating the first word as a unit before falling back on the whole
+    // thing as a food name. a known unit that does not fit the food is flagged
+    // rather than taken as part of the name
+    if let Some((unit, name)) = rest.split_first() {
+        if let Some(food) = find_food(name, foods) {
+            if same_unit(unit, &food.unit) || units::is_known(unit) {
+                let quantity = in_food_unit(quantity, unit, food)?;
+                return Ok(Entry::new(food.clone(), quantity));
+            }
+        }
+    }
+    find_food(rest, foods)
+        .map(|food| Entry::new(food.clone(), quantity))
+        .ok_or_else(|| format!("no food matching `{}`", rest.join(" ")))
+}
+
+/// resolve a food given by `name` and optionally `unit`, as when they are
+/// passed separately instead of in a description, preferring a food with
+/// exactly that name
+pub fn resolve_named(
+    name: &str,
+    unit: Option<&str>,
+    quantity: f64,
+    foods: &[Food],
+) -> Result<Entry, String> {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let food = foods
+        .iter()
+        .find(|food| food.name.eq_ignore_ascii_case(name.trim()))
+        .or_else(|| find_food(&words, foods))
+        .ok_or_else(|| format!("no food matching `{name}`"))?;
+    let quantity = match unit {
+        Some(unit) => in_food_unit(quantity, unit, food)?,
+        None => quantity,
+    };
+    Ok(Entry::new(food.clone(), quantity))
+}
+
+/// parse a comma-separated list of entries like "2 eggs, 40g oats" by matching
+/// each item's quantity, unit, and name against `foods`
+pub fn parse_entries(s: &str, foods: &[Food]) -> Vec<Resolved> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| Resolved {
+            input: item.to_owned(),
+            result: resolve(item, foods),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixed_number_with_unit() {
+        assert_eq!(quantity("1 1/2 cup", "Cup"), Ok(1.5));
+        assert_eq!(quantity("2 1/4 Cup", "Cup"), Ok(2.25));
+    }
+
+    #[test]
+    fn number_with_unit() {
+        assert_eq!(quantity("3", "g"), Ok(3.0));
+        assert_eq!(quantity("3 g", "g"), Ok(3.0));
+        assert!(quantity("1 1/2 cup", "g").is_err());
+    }
+
+    #[test]
+    fn quantity_not_positive() {
+        let foods: Vec<Food> =
+            ["Egg\t70\t5\t0\t6\tegg", "Rice\t130\t0\t28\t3\tg"]
+                .iter()
+                .map(|line| line.parse().unwrap())
+                .collect();
+        for input in ["-2 eggs", "0 rice", "0g rice"] {
+            let resolved = parse_entries(input, &foods);
+            assert!(resolved[0].result.is_err(), "{input}");
+        }
+        let resolved = parse_entries("2 eggs, 50g rice", &foods);
+        assert!(resolved.iter().all(|r| r.result.is_ok()));
+    }
+}
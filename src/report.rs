@@ -0,0 +1,1122 @@
+//! plain-text reports over the food database and log
+
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike, Weekday};
+
+use crate::{
+    custom::{Group, Spec},
+    events::Events,
+    log::{Entry, Log, DATE_FMT},
+    model,
+    targets::Targets,
+    Food, Macros, NUTRIENTS,
+};
+
+/// report how many foods are missing each of the optional [NUTRIENTS], as an
+/// indication of how far totals of those nutrients can be trusted
+pub fn completeness(foods: &[Food]) -> Vec<String> {
+    let total = foods.len();
+    let mut ret = vec![format!("{total} foods in the database"), String::new()];
+    for (i, name) in NUTRIENTS.iter().enumerate() {
+        let missing = foods.iter().filter(|f| f.nutrients[i].is_none()).count();
+        let pct = if total == 0 {
+            0.0
+        } else {
+            100.0 * missing as f64 / total as f64
+        };
+        ret.push(format!(
+            "{name:<8} missing for {missing:>4} of {total} foods ({pct:.0}%)"
+        ));
+    }
+    let none = foods
+        .iter()
+        .filter(|f| f.nutrients.iter().all(Option::is_none))
+        .count();
+    ret.push(String::new());
+    ret.push(format!("{none} foods have no optional nutrients at all"));
+    ret
+}
+
+/// the parts of the day that [time_of_day] groups entries into, as the name and
+/// the hour each part starts
+const BUCKETS: [(&str, u32); 4] = [
+    ("Night", 0),
+    ("Morning", 5),
+    ("Afternoon", 11),
+    ("Evening", 17),
+];
+
+/// the hour after which entries count as night again
+const NIGHT_START: u32 = 22;
+
+/// the index in [BUCKETS] of the part of the day containing `time`
+fn bucket(time: NaiveTime) -> usize {
+    let hour = time.hour();
+    if hour >= NIGHT_START {
+        0
+    } else {
+        BUCKETS.iter().rposition(|(_, h)| hour >= *h).unwrap()
+    }
+}
+
+/// the name of the part of the day containing `time`, which stands in for the
+/// meal an entry belongs to
+pub fn part_of_day(time: NaiveTime) -> &'static str {
+    BUCKETS[bucket(time)].0
+}
+
+/// the names of the meals totaled by [by_meal]: the parts of the day in
+/// [BUCKETS], then entries without a time
+fn meal_names() -> impl Iterator<Item = &'static str> {
+    BUCKETS.iter().map(|(name, _)| *name).chain(["Untimed"])
+}
+
+/// total the macros eaten in `entries` by meal, in the order of [meal_names]
+fn by_meal<'a>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+) -> [Macros; BUCKETS.len() + 1] {
+    let mut ret = [Macros::default(); BUCKETS.len() + 1];
+    for entry in entries {
+        ret[entry.time.map_or(BUCKETS.len(), bucket)] += entry.macros();
+    }
+    ret
+}
+
+/// report the average calories and macros eaten in each part of the day over
+/// the days from `from` to `to` that have any entries
+pub fn time_of_day(log: &Log, from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let days = log.range(from, to).count();
+    if days == 0 {
+        return vec!["no entries in this period".to_owned()];
+    }
+
+    let totals = by_meal(log.range(from, to).flat_map(|(_, e)| e));
+    let all: f64 = totals.iter().map(|m| m.calories).sum();
+    let mut ret = vec![
+        format!("averages over {days} logged days"),
+        String::new(),
+        format!(
+            "{:<10}{:>9}{:>7}{:>9}{:>7}{:>7}",
+            "", "Calories", "Share", "Protein", "Carbs", "Fat"
+        ),
+    ];
+    for (name, m) in meal_names().zip(&totals) {
+        let share = if all > 0.0 {
+            100.0 * m.calories / all
+        } else {
+            0.0
+        };
+        let d = days as f64;
+        ret.push(format!(
+            "{name:<10}{:>9.0}{:>6.0}%{:>9.0}{:>7.0}{:>7.0}",
+            m.calories / d,
+            share,
+            m.protein / d,
+            m.carbs / d,
+            m.fat / d,
+        ));
+    }
+    ret
+}
+
+/// report the average calories and macros eaten on each day of the week over
+/// the logged days from `from` to `to`, and on weekdays as a whole against
+/// weekends. with calorie goals, also count the days over budget and flag the
+/// days that break it more often than not
+pub fn weekdays(
+    log: &Log,
+    from: NaiveDate,
+    to: NaiveDate,
+    targets: &Targets,
+) -> Vec<String> {
+    let budget = targets.defaults().calories;
+    let mut totals: [Macros; 7] = Default::default();
+    let mut days = [0; 7];
+    let mut over = [0; 7];
+    for (date, entries) in log.range(from, to) {
+        if entries.is_empty() {
+            continue;
+        }
+        let day = date.weekday().num_days_from_monday() as usize;
+        let total = log.totals(date);
+        days[day] += 1;
+        if targets
+            .get(date)
+            .calories
+            .is_some_and(|b| total.calories > b)
+        {
+            over[day] += 1;
+        }
+        totals[day] += total;
+    }
+    if days.iter().all(|&d| d == 0) {
+        return vec!["no entries in this period".to_owned()];
+    }
+
+    let mut ret = Vec::new();
+    match budget {
+        Some(b) => ret.push(format!("budget of {b:.0} calories per day")),
+        None => {
+            ret.push("set `budget` in the config to track days over".into())
+        }
+    }
+    ret.push(String::new());
+    ret.push(format!(
+        "{:<8}{:>6}{:>9}{:>9}{:>7}{:>7}{:>6}",
+        "", "Days", "Calories", "Protein", "Carbs", "Fat", "Over"
+    ));
+    let row = |label: &str, n: usize, m: Macros, over: usize| {
+        let d = n.max(1) as f64;
+        let mut line = format!(
+            "{label:<8}{n:>6}{:>9.0}{:>9.0}{:>7.0}{:>7.0}",
+            m.calories / d,
+            m.protein / d,
+            m.carbs / d,
+            m.fat / d,
+        );
+        if budget.is_some() {
+            line.push_str(&format!("{over:>6}"));
+            if 2 * over > n {
+                line.push_str("  !");
+            }
+        }
+        line
+    };
+    let mut day = Weekday::Mon;
+    for i in 0..7 {
+        ret.push(row(&day.to_string(), days[i], totals[i], over[i]));
+        day = day.succ();
+    }
+
+    // weekdays are Monday to Friday, the first five of each array
+    let mut parts = [(0, Macros::default(), 0); 2];
+    for i in 0..7 {
+        let part = &mut parts[usize::from(i >= 5)];
+        part.0 += days[i];
+        part.1 += totals[i];
+        part.2 += over[i];
+    }
+    ret.push(String::new());
+    for (label, (n, m, over)) in ["Mon-Fri", "Sat-Sun"].iter().zip(parts) {
+        ret.push(row(label, n, m, over));
+    }
+    let [(weekdays, week, _), (weekends, weekend, _)] = parts;
+    if weekdays > 0 && weekends > 0 {
+        let week = week.calories / weekdays as f64;
+        let weekend = weekend.calories / weekends as f64;
+        let diff = weekend - week;
+        let more = if diff >= 0.0 { "more" } else { "less" };
+        ret.push(String::new());
+        ret.push(format!(
+            "weekends average {:.0} calories {more} than weekdays",
+            diff.abs()
+        ));
+    }
+    ret
+}
+
+/// the width of the bars drawn by [split_line]
+const SPLIT_WIDTH: usize = 20;
+
+/// the percentages of the calories in `m` that come from protein, carbs, and
+/// fat, counting 4 calories per gram of protein and carbs and 9 per gram of
+/// fat. this is None if `m` has none of them
+pub fn macro_split(m: &Macros) -> Option<[f64; 3]> {
+    let calories = [4.0 * m.protein, 4.0 * m.carbs, 9.0 * m.fat];
+    let total: f64 = calories.iter().sum();
+    (total > 0.0).then(|| calories.map(|c| 100.0 * c / total))
+}
+
+/// the percentages from [macro_split] followed by a bar stacking a `P`, `C`, or
+/// `F` for each part of the split
+pub fn split_line(split: [f64; 3]) -> String {
+    let mut bar = String::new();
+    let mut sum = 0.0;
+    for (pct, c) in split.iter().zip(['P', 'C', 'F']) {
+        // round the running total so that the parts always fill the bar
+        sum += pct;
+        let end = (sum * SPLIT_WIDTH as f64 / 100.0).round() as usize;
+        while bar.len() < end {
+            bar.push(c);
+        }
+    }
+    format!(
+        "P {:.0}% C {:.0}% F {:.0}% [{bar}]",
+        split[0], split[1], split[2]
+    )
+}
+
+/// report the split of calories between protein, carbs, and fat over the days
+/// from `from` to `to` as a whole and on each logged day
+pub fn split(log: &Log, from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let days: Vec<(NaiveDate, Macros)> = log
+        .range(from, to)
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(date, _)| (date, log.totals(date)))
+        .collect();
+    let mut total = Macros::default();
+    for (_, m) in &days {
+        total += *m;
+    }
+    let Some(overall) = macro_split(&total) else {
+        return vec!["no entries in this period".to_owned()];
+    };
+    let mut ret = vec![
+        format!("over {} logged days", days.len()),
+        format!("  {}", split_line(overall)),
+        String::new(),
+    ];
+    for (date, m) in days.iter().rev() {
+        if let Some(split) = macro_split(m) {
+            let date = date.format("%a %Y-%m-%d");
+            ret.push(format!("{date}  {}", split_line(split)));
+        }
+    }
+    ret
+}
+
+/// the least fiber in grams worth eating in a day, for [assess]
+const FIBER_MIN: f64 = 20.0;
+/// the most sodium in milligrams worth eating in a day, for [assess]
+const SODIUM_MAX: f64 = 2300.0;
+/// the most sugar in grams worth eating in a day, for [assess]
+const SUGAR_MAX: f64 = 50.0;
+
+/// the total of the optional nutrient `name` over `entries`, or None if it is
+/// not known for any of them
+fn nutrient_total(entries: &[Entry], name: &str) -> Option<f64> {
+    let i = NUTRIENTS.iter().position(|n| *n == name)?;
+    entries
+        .iter()
+        .filter_map(|e| Some(e.food.nutrients[i]? * e.eaten()))
+        .reduce(|a, b| a + b)
+}
+
+/// explain what stands out about the entries on `date`, by simple rules over
+/// its totals, goals, meals, and optional nutrients, like `protein low because
+/// Evening was carb-heavy` or `fiber under 20 g`
+pub fn assess(log: &Log, date: NaiveDate, targets: &Targets) -> Vec<String> {
+    let entries = log.day(date);
+    if entries.is_empty() {
+        return Vec::new();
+    }
+    let total = log.totals(date);
+    let goals = targets.get(date);
+    let meals = by_meal(entries);
+    let mut ret = Vec::new();
+
+    if let Some(goal) = goals.calories {
+        let diff = total.calories - goal;
+        if diff > 0.1 * goal {
+            ret.push(format!("calories {diff:.0} over the goal of {goal:.0}"));
+        } else if -diff > 0.25 * goal {
+            ret.push(format!(
+                "calories {:.0} under the goal of {goal:.0}, was everything \
+                 logged?",
+                -diff
+            ));
+        }
+    }
+
+    let split = macro_split(&total);
+    let protein_low = match goals.protein {
+        Some(goal) => total.protein < 0.9 * goal,
+        None => split.is_some_and(|s| s[0] < 15.0),
+    };
+    if protein_low {
+        // blame the biggest meal if most of it was carbs
+        let heaviest = meal_names()
+            .zip(&meals)
+            .max_by(|a, b| a.1.calories.total_cmp(&b.1.calories))
+            .and_then(|(name, m)| Some((name, macro_split(m)?)))
+            .filter(|(_, s)| s[1] > 55.0);
+        let amount = match goals.protein {
+            Some(goal) => format!("{:.0} of {goal:.0} g", total.protein),
+            None => format!("{:.0}% of calories", split.unwrap()[0]),
+        };
+        ret.push(match heaviest {
+            Some((name, s)) => format!(
+                "protein low ({amount}) because {name} was carb-heavy \
+                 (C {:.0}%)",
+                s[1]
+            ),
+            None => format!("protein low ({amount})"),
+        });
+    }
+    if let Some(s) = split.filter(|s| s[2] > 40.0) {
+        ret.push(format!("fat-heavy day (F {:.0}%)", s[2]));
+    }
+    if total.calories > 0.0 && meals[0].calories > 0.3 * total.calories {
+        ret.push(format!(
+            "{:.0}% of calories eaten at night",
+            100.0 * meals[0].calories / total.calories
+        ));
+    }
+
+    if let Some(fiber) = nutrient_total(entries, "Fiber") {
+        if fiber < FIBER_MIN {
+            ret.push(format!("fiber under {FIBER_MIN:.0} g ({fiber:.0} g)"));
+        }
+    }
+    if let Some(sugar) = nutrient_total(entries, "Sugar") {
+        if sugar > SUGAR_MAX {
+            ret.push(format!("sugar over {SUGAR_MAX:.0} g ({sugar:.0} g)"));
+        }
+    }
+    if let Some(sodium) = nutrient_total(entries, "Sodium") {
+        if sodium > SODIUM_MAX {
+            ret.push(format!(
+                "sodium over {SODIUM_MAX:.0} mg ({sodium:.0} mg)"
+            ));
+        }
+    }
+    if ret.is_empty() {
+        ret.push("nothing stands out".to_owned());
+    }
+    ret
+}
+
+/// summarize `date`: the totals against its goals, the macro split, the totals
+/// of each meal, and, if `explain` is set, the [assess]ment of the day
+pub fn day(
+    log: &Log,
+    date: NaiveDate,
+    targets: &Targets,
+    explain: bool,
+) -> Vec<String> {
+    let entries = log.day(date);
+    if entries.is_empty() {
+        return vec!["no entries on this day".to_owned()];
+    }
+    let total = log.totals(date);
+    let goals = targets.get(date);
+    let mut ret = vec![format!("{} entries", entries.len()), String::new()];
+    let eaten = [total.calories, total.protein, total.carbs, total.fat];
+    let names = ["Calories", "Protein", "Carbs", "Fat"];
+    for ((name, eaten), goal) in names.iter().zip(eaten).zip(goals.fields()) {
+        ret.push(match goal {
+            Some(goal) => format!("{name:>8}: {eaten:.0} / {goal:.0}"),
+            None => format!("{name:>8}: {eaten:.0}"),
+        });
+    }
+    if let Some(split) = macro_split(&total) {
+        ret.push(format!("{:>8}: {}", "Split", split_line(split)));
+    }
+    ret.push(String::new());
+    ret.push("by meal".to_owned());
+    for (name, m) in meal_names().zip(by_meal(entries)) {
+        if m.calories == 0.0 {
+            continue;
+        }
+        let line = format!(
+            "Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            m.calories, m.protein, m.carbs, m.fat
+        );
+        ret.push(format!("  {name:<10}{line}"));
+    }
+    if explain {
+        ret.push(String::new());
+        ret.push("assessment".to_owned());
+        ret.extend(assess(log, date, targets).iter().map(|s| format!("  {s}")));
+    }
+    ret
+}
+
+/// the width of the longest bar in [calories]
+const BAR_WIDTH: usize = 40;
+
+/// chart the calories logged on each day from `from` to `to` as a bar per day,
+/// marking each day's calorie goal on its bar and listing any `events` beside
+/// it
+pub fn calories(
+    log: &Log,
+    events: &Events,
+    from: NaiveDate,
+    to: NaiveDate,
+    targets: &Targets,
+) -> Vec<String> {
+    let days: Vec<(NaiveDate, f64)> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| (date, log.totals(date).calories))
+        .filter(|(date, cals)| *cals > 0.0 || !events.day(*date).is_empty())
+        .collect();
+    if days.is_empty() {
+        return vec!["no entries in this period".to_owned()];
+    }
+    let max = days
+        .iter()
+        .flat_map(|(date, c)| [Some(*c), targets.get(*date).calories])
+        .flatten()
+        .fold(1.0, f64::max);
+    let scale = |cals: f64| (cals / max * BAR_WIDTH as f64).round() as usize;
+    let mut ret = Vec::new();
+    for (date, cals) in days {
+        let mut bar: Vec<char> = "█"
+            .repeat(scale(cals))
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(BAR_WIDTH + 1)
+            .collect();
+        if let Some(b) = targets.get(date).calories {
+            bar[scale(b)] = '|';
+        }
+        let bar: String = bar.into_iter().collect();
+        let mut line = format!("{} {bar} {cals:>5.0}", date.format(DATE_FMT));
+        for event in events.day(date) {
+            line.push_str(&format!("  ◆ {event}"));
+        }
+        ret.push(line);
+    }
+    ret
+}
+
+/// summarize the month starting on `first`: average daily calories and macros
+/// over the logged days, the days over and under the calorie goal, and the
+/// heaviest and lightest days
+pub fn monthly(log: &Log, first: NaiveDate, targets: &Targets) -> Vec<String> {
+    let days: Vec<(NaiveDate, Macros)> = first
+        .iter_days()
+        .take_while(|date| date.month() == first.month())
+        .filter(|date| !log.day(*date).is_empty())
+        .map(|date| (date, log.totals(date)))
+        .collect();
+    let length = first
+        .iter_days()
+        .take_while(|date| date.month() == first.month())
+        .count();
+    if days.is_empty() {
+        return vec!["no entries in this month".to_owned()];
+    }
+
+    let mut total = Macros::default();
+    for (_, m) in &days {
+        total += *m;
+    }
+    let n = days.len() as f64;
+    let mut ret = vec![
+        format!("{} of {length} days logged", days.len()),
+        String::new(),
+        "average per logged day".to_owned(),
+        format!(
+            "  Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            total.calories / n,
+            total.protein / n,
+            total.carbs / n,
+            total.fat / n
+        ),
+        String::new(),
+    ];
+    let goals: Vec<_> = days
+        .iter()
+        .filter_map(|(date, m)| Some((m, targets.get(*date).calories?)))
+        .collect();
+    if !goals.is_empty() {
+        let over = goals.iter().filter(|(m, b)| m.calories > *b).count();
+        let under = goals.len() - over;
+        ret.push("calorie goal".to_owned());
+        ret.push(format!("  {over} days over, {under} days under"));
+        ret.push(String::new());
+    }
+    ret.push("average per logged day by meal".to_owned());
+    let meals = by_meal(days.iter().flat_map(|(date, _)| log.day(*date)));
+    for (name, m) in meal_names().zip(meals) {
+        if m.calories == 0.0 {
+            continue;
+        }
+        let line = format!(
+            "Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            m.calories / n,
+            m.protein / n,
+            m.carbs / n,
+            m.fat / n
+        );
+        ret.push(format!("  {name:<10}{line}"));
+    }
+    ret.push(String::new());
+    let by_calories = |a: &&(NaiveDate, Macros), b: &&(NaiveDate, Macros)| {
+        a.1.calories.total_cmp(&b.1.calories)
+    };
+    for (label, day) in [
+        ("heaviest", days.iter().max_by(by_calories)),
+        ("lightest", days.iter().min_by(by_calories)),
+    ] {
+        if let Some((date, m)) = day {
+            ret.push(format!(
+                "{label} day: {} ({:.0} calories)",
+                date.format("%a %Y-%m-%d"),
+                m.calories
+            ));
+        }
+    }
+    ret
+}
+
+/// the number of foods listed in the top foods table of [markdown], the
+/// most-logged foods of [yearly], and each list of [top_foods]
+const TOP_FOODS: usize = 10;
+
+/// the name of each food in `entries`, with the number of times it was logged
+/// and the calories eaten from it, in the order the foods first appear
+fn food_totals<'a>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+) -> Vec<(&'a str, usize, f64)> {
+    let mut foods: Vec<(&str, usize, f64)> = Vec::new();
+    for entry in entries {
+        let calories = entry.macros().calories;
+        match foods.iter_mut().find(|(name, ..)| *name == entry.food.name) {
+            Some((_, n, total)) => {
+                *n += 1;
+                *total += calories;
+            }
+            None => foods.push((&entry.food.name, 1, calories)),
+        }
+    }
+    foods
+}
+
+/// report the foods that contributed the most calories and the foods logged
+/// most often over the days from `from` to `to`
+pub fn top_foods(log: &Log, from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let mut foods =
+        food_totals(log.range(from, to).flat_map(|(_, entries)| entries));
+    if foods.is_empty() {
+        return vec!["no entries in this period".to_owned()];
+    }
+    let all: f64 = foods.iter().map(|(_, _, calories)| calories).sum();
+    let share = |calories: f64| {
+        if all > 0.0 {
+            100.0 * calories / all
+        } else {
+            0.0
+        }
+    };
+    let header =
+        format!("{:>9}{:>7}{:>7}  {}", "Calories", "Share", "Times", "Food");
+    let row = |(name, n, calories): &(&str, usize, f64)| {
+        format!("{calories:>9.0}{:>6.0}%{n:>7}  {name}", share(*calories))
+    };
+
+    let mut ret = vec!["most calories".to_owned(), header.clone()];
+    foods.sort_by(|a, b| b.2.total_cmp(&a.2));
+    ret.extend(foods.iter().take(TOP_FOODS).map(row));
+    ret.push(String::new());
+    ret.push("most often".to_owned());
+    ret.push(header);
+    foods.sort_by_key(|(_, n, _)| std::cmp::Reverse(*n));
+    ret.extend(foods.iter().take(TOP_FOODS).map(row));
+    ret
+}
+
+/// summarize `year`: how many days were logged, the average daily intake
+/// overall and by month, the longest run of logged days, and the foods logged
+/// most often
+pub fn yearly(log: &Log, year: i32) -> Vec<String> {
+    let (Some(first), Some(last)) = (
+        NaiveDate::from_ymd_opt(year, 1, 1),
+        NaiveDate::from_ymd_opt(year, 12, 31),
+    ) else {
+        return vec!["no entries in this year".to_owned()];
+    };
+    let days: Vec<(NaiveDate, &[Entry])> = log
+        .range(first, last)
+        .filter(|(_, entries)| !entries.is_empty())
+        .collect();
+    if days.is_empty() {
+        return vec!["no entries in this year".to_owned()];
+    }
+
+    let length = first.iter_days().take_while(|d| *d <= last).count();
+    let n = days.len() as f64;
+    let mut total = Macros::default();
+    for (date, _) in &days {
+        total += log.totals(*date);
+    }
+    let entries: usize = days.iter().map(|(_, e)| e.len()).sum();
+    let mut ret = vec![
+        format!("{} of {length} days logged, {entries} entries", days.len()),
+        String::new(),
+        "average per logged day".to_owned(),
+        format!(
+            "  Calories: {:.0} Protein: {:.0} Carbs: {:.0} Fat: {:.0}",
+            total.calories / n,
+            total.protein / n,
+            total.carbs / n,
+            total.fat / n
+        ),
+        String::new(),
+    ];
+
+    // the longest run of consecutive logged days, as its first and last day
+    let mut best = (days[0].0, days[0].0);
+    let mut start = days[0].0;
+    for pair in days.windows(2) {
+        let (prev, date) = (pair[0].0, pair[1].0);
+        if prev.succ_opt() != Some(date) {
+            start = date;
+        }
+        if date - start > best.1 - best.0 {
+            best = (start, date);
+        }
+    }
+    ret.push(format!(
+        "longest streak: {} days, {} to {}",
+        (best.1 - best.0).num_days() + 1,
+        best.0.format(DATE_FMT),
+        best.1.format(DATE_FMT)
+    ));
+    ret.push(String::new());
+
+    ret.push(format!("{:<6}{:>6}{:>9}", "", "Days", "Calories"));
+    for month in 1..=12 {
+        let month_days: Vec<_> =
+            days.iter().filter(|(d, _)| d.month() == month).collect();
+        if month_days.is_empty() {
+            continue;
+        }
+        let calories: f64 = month_days
+            .iter()
+            .map(|(d, _)| log.totals(*d).calories)
+            .sum();
+        let name = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        ret.push(format!(
+            "{:<6}{:>6}{:>9.0}",
+            name.format("%b").to_string(),
+            month_days.len(),
+            calories / month_days.len() as f64
+        ));
+    }
+    ret.push(String::new());
+
+    ret.push("most logged foods".to_owned());
+    let mut foods =
+        food_totals(days.iter().flat_map(|(_, entries)| entries.iter()));
+    // a stable sort keeps ties in the order the foods were first logged
+    foods.sort_by_key(|(_, n, _)| std::cmp::Reverse(*n));
+    for (name, n, _) in foods.into_iter().take(TOP_FOODS) {
+        ret.push(format!("  {n:>4}  {name}"));
+    }
+    ret
+}
+
+/// a Markdown report of `days`, from `from` to `to`, with a table of daily
+/// totals, a table of average daily totals for each week starting on Monday,
+/// the average totals of each meal, and the foods that contributed the most
+/// calories
+pub fn markdown(
+    days: &[model::Day],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<String> {
+    let days: Vec<&model::Day> =
+        days.iter().filter(|day| !day.entries.is_empty()).collect();
+    let mut ret = vec![
+        format!(
+            "# Nutrition report, {} to {}",
+            from.format(DATE_FMT),
+            to.format(DATE_FMT)
+        ),
+        String::new(),
+    ];
+    if days.is_empty() {
+        ret.push("No entries in this period.".to_owned());
+        return ret;
+    }
+
+    let row = |label: String, n: usize, m: model::Totals| {
+        format!(
+            "| {label} | {n} | {:.0} | {:.0} | {:.0} | {:.0} |",
+            m.calories, m.protein, m.carbs, m.fat
+        )
+    };
+
+    ret.push("## Daily totals".to_owned());
+    ret.push(String::new());
+    ret.push("| Date | Entries | Calories | Protein | Carbs | Fat |".into());
+    ret.push("|---|--:|--:|--:|--:|--:|".to_owned());
+    for day in &days {
+        let label = day.naive_date().format("%a %Y-%m-%d").to_string();
+        ret.push(row(label, day.entries.len(), day.totals));
+    }
+
+    ret.push(String::new());
+    ret.push("## Weekly averages".to_owned());
+    ret.push(String::new());
+    ret.push("| Week of | Days | Calories | Protein | Carbs | Fat |".into());
+    ret.push("|---|--:|--:|--:|--:|--:|".to_owned());
+    let mut weeks: Vec<(NaiveDate, usize, model::Totals)> = Vec::new();
+    for day in &days {
+        let monday = day.naive_date().week(Weekday::Mon).first_day();
+        match weeks.last_mut() {
+            Some((week, n, total)) if *week == monday => {
+                *n += 1;
+                *total += day.totals;
+            }
+            _ => weeks.push((monday, 1, day.totals)),
+        }
+    }
+    for (week, n, total) in weeks {
+        let d = n as f64;
+        let avg = model::Totals {
+            calories: total.calories / d,
+            protein: total.protein / d,
+            carbs: total.carbs / d,
+            fat: total.fat / d,
+        };
+        ret.push(row(week.format(DATE_FMT).to_string(), n, avg));
+    }
+
+    let entries = || days.iter().flat_map(|day| &day.entries);
+
+    ret.push(String::new());
+    ret.push("## Daily averages by meal".to_owned());
+    ret.push(String::new());
+    ret.push("| Meal | Calories | Share | Protein | Carbs | Fat |".into());
+    ret.push("|---|--:|--:|--:|--:|--:|".to_owned());
+    let mut meals = [model::Totals::default(); BUCKETS.len() + 1];
+    for entry in entries() {
+        let meal = entry.meal.as_deref().unwrap_or("Untimed");
+        if let Some(i) = meal_names().position(|name| name == meal) {
+            meals[i] += entry.totals();
+        }
+    }
+    let all: f64 = meals.iter().map(|m| m.calories).sum();
+    let d = days.len() as f64;
+    for (name, m) in meal_names().zip(meals) {
+        if m.calories == 0.0 {
+            continue;
+        }
+        ret.push(format!(
+            "| {name} | {:.0} | {:.0}% | {:.0} | {:.0} | {:.0} |",
+            m.calories / d,
+            100.0 * m.calories / all,
+            m.protein / d,
+            m.carbs / d,
+            m.fat / d,
+        ));
+    }
+
+    ret.push(String::new());
+    ret.push("## Top foods by calories".to_owned());
+    ret.push(String::new());
+    ret.push("| Food | Times logged | Calories |".to_owned());
+    ret.push("|---|--:|--:|".to_owned());
+    let mut foods: Vec<(&str, usize, f64)> = Vec::new();
+    for entry in entries() {
+        let calories = entry.totals().calories;
+        match foods.iter_mut().find(|(name, ..)| *name == entry.food.name) {
+            Some((_, n, total)) => {
+                *n += 1;
+                *total += calories;
+            }
+            None => foods.push((&entry.food.name, 1, calories)),
+        }
+    }
+    foods.sort_by(|a, b| b.2.total_cmp(&a.2));
+    for (name, n, calories) in foods.into_iter().take(TOP_FOODS) {
+        let name = name.replace('|', "\\|");
+        ret.push(format!("| {name} | {n} | {calories:.0} |"));
+    }
+    ret
+}
+
+/// the characters used to draw sparklines, from lowest to highest
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// draw sparklines of daily calories and macros from `from` to `to`, at most
+/// `width` characters wide. when there are more days than fit, each character
+/// averages several days, and days without entries are left out
+pub fn trends(
+    log: &Log,
+    from: NaiveDate,
+    to: NaiveDate,
+    width: usize,
+) -> Vec<String> {
+    let days: Vec<Option<[f64; 4]>> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let m = log.totals(date);
+            let values = [m.calories, m.protein, m.carbs, m.fat];
+            (!log.day(date).is_empty()).then_some(values)
+        })
+        .collect();
+    if days.iter().all(Option::is_none) {
+        return vec!["no entries in this period".to_owned()];
+    }
+    let width = width.clamp(1, days.len());
+    let per_column = days.len().div_ceil(width);
+
+    let mut ret = Vec::new();
+    let names = ["Calories", "Protein", "Carbs", "Fat"];
+    for (k, name) in names.iter().enumerate() {
+        let columns: Vec<Option<f64>> = days
+            .chunks(per_column)
+            .map(|chunk| {
+                let values: Vec<f64> =
+                    chunk.iter().flatten().map(|v| v[k]).collect();
+                (!values.is_empty())
+                    .then(|| values.iter().sum::<f64>() / values.len() as f64)
+            })
+            .collect();
+        // the summary is over single days, but the scale is over the columns
+        let values: Vec<f64> = days.iter().flatten().map(|v| v[k]).collect();
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let avg = values.iter().sum::<f64>() / values.len() as f64;
+        let day_max = values.iter().copied().fold(0.0, f64::max);
+        let max = columns.iter().flatten().copied().fold(0.0, f64::max);
+        let line: String = columns
+            .iter()
+            .map(|v| match v {
+                Some(v) if max > 0.0 => {
+                    let i = (v / max * (SPARKS.len() - 1) as f64).round();
+                    SPARKS[i as usize]
+                }
+                Some(_) => SPARKS[0],
+                None => ' ',
+            })
+            .collect();
+        ret.push(format!(
+            "{name}: min {min:.0}, average {avg:.0}, max {day_max:.0}"
+        ));
+        ret.push(line);
+        ret.push(String::new());
+    }
+    if per_column > 1 {
+        ret.push(format!("each column averages {per_column} days"));
+    }
+    ret
+}
+
+/// quote `field` for a csv file if it contains a separator, quote, or newline
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// one csv row per entry in `days`, after a header row. the meal is the part
+/// of the day the entry was eaten in, and empty for untimed entries. the
+/// macros are the amounts actually eaten
+pub fn csv(days: &[model::Day]) -> Vec<String> {
+    let mut ret = vec![
+        "date,time,meal,food,unit,quantity,consumed,calories,protein,carbs,fat"
+            .to_owned(),
+    ];
+    for day in days {
+        for entry in &day.entries {
+            let m = entry.totals();
+            ret.push(format!(
+                "{},{},{},{},{},{},{},{:.1},{:.1},{:.1},{:.1}",
+                day.date,
+                entry.time.as_deref().unwrap_or_default(),
+                entry.meal.as_deref().unwrap_or_default(),
+                csv_field(&entry.food.name),
+                csv_field(&entry.food.unit),
+                entry.quantity,
+                entry.consumed,
+                m.calories,
+                m.protein,
+                m.carbs,
+                m.fat,
+            ));
+        }
+    }
+    ret
+}
+
+/// a row of a custom report: its label, the sums of each metric, the number of
+/// days to average them over, and the number of entries that went into it
+struct Row {
+    label: String,
+    sums: Vec<f64>,
+    days: usize,
+    entries: usize,
+}
+
+/// run the custom report `spec` over its range of days ending on `to`
+pub fn custom(log: &Log, spec: &Spec, to: NaiveDate) -> Vec<String> {
+    let (from, to) = spec.range.dates(log, to);
+    let days: Vec<(NaiveDate, &[Entry])> = log
+        .range(from, to)
+        .filter(|(_, entries)| !entries.is_empty())
+        .collect();
+    if days.is_empty() {
+        return vec!["no entries in this period".to_owned()];
+    }
+
+    let new_row = |label: String| Row {
+        label,
+        sums: vec![0.0; spec.metrics.len()],
+        days: 0,
+        entries: 0,
+    };
+    let mut rows: Vec<Row> = match spec.group {
+        Group::Weekday => {
+            let mut day = Weekday::Mon;
+            (0..7)
+                .map(|_| {
+                    let row = new_row(day.to_string());
+                    day = day.succ();
+                    row
+                })
+                .collect()
+        }
+        Group::Meal => meal_names().map(|m| new_row(m.to_owned())).collect(),
+        _ => Vec::new(),
+    };
+    for (date, entries) in &days {
+        let mut seen = Vec::new();
+        for entry in entries.iter() {
+            let label = match spec.group {
+                Group::Day => date.format("%a %Y-%m-%d").to_string(),
+                Group::Week => {
+                    let monday = date.week(Weekday::Mon).first_day();
+                    format!("week of {}", monday.format(DATE_FMT))
+                }
+                Group::Month => date.format("%b %Y").to_string(),
+                Group::Weekday => date.weekday().to_string(),
+                Group::Meal => {
+                    entry.time.map_or("Untimed", part_of_day).to_owned()
+                }
+                Group::Food => entry.food.name.clone(),
+            };
+            let i = match rows.iter().position(|row| row.label == label) {
+                Some(i) => i,
+                None => {
+                    rows.push(new_row(label));
+                    rows.len() - 1
+                }
+            };
+            let row = &mut rows[i];
+            for (sum, metric) in row.sums.iter_mut().zip(&spec.metrics) {
+                *sum += entry.variable(metric).unwrap_or(0.0);
+            }
+            row.entries += 1;
+            if !seen.contains(&i) {
+                row.days += 1;
+                seen.push(i);
+            }
+        }
+    }
+    rows.retain(|row| row.entries > 0);
+    match spec.group {
+        // meals are averaged over every logged day, not just those with the
+        // meal, so that they add up to the daily average
+        Group::Meal => rows.iter_mut().for_each(|row| row.days = days.len()),
+        Group::Food => {
+            rows.iter_mut().for_each(|row| row.days = 1);
+            rows.sort_by(|a, b| b.sums[0].total_cmp(&a.sums[0]));
+        }
+        _ => {}
+    }
+
+    let width = rows.iter().map(|row| row.label.chars().count()).max();
+    let width = width.unwrap_or(0).max(5) + 2;
+    let mut header = format!("{:width$}", "");
+    for metric in &spec.metrics {
+        let mut name: Vec<char> = metric.chars().collect();
+        name[0] = name[0].to_ascii_uppercase();
+        let name: String = name.into_iter().collect();
+        header.push_str(&format!("{name:>10}"));
+    }
+    let mut ret = vec![
+        format!(
+            "{} logged days from {} to {}",
+            days.len(),
+            from.format(DATE_FMT),
+            to.format(DATE_FMT)
+        ),
+        String::new(),
+        header,
+    ];
+    for row in rows {
+        let mut line = format!("{:width$}", row.label);
+        for sum in row.sums {
+            line.push_str(&format!("{:>10.0}", sum / row.days as f64));
+        }
+        ret.push(line);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::TIME_FMT;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FMT).unwrap()
+    }
+
+    /// a log of a bun and some rice on Monday and a bun on Tuesday, in memory
+    fn log() -> Log {
+        // the directory does not exist, so this loads an empty log
+        let dir = std::env::temp_dir()
+            .join(format!("macroni-report-test-{}", std::process::id()));
+        let mut log = Log::load(dir.join("entries")).unwrap();
+        let bun: Food = "Bun\t140\t25\t2\t4\tbun".parse().unwrap();
+        let rice: Food = "Rice\t200\t45\t0\t4\tcup".parse().unwrap();
+        for (day, time, food, quantity) in [
+            ("2024-01-01", "08:00", &bun, 2.0),
+            ("2024-01-01", "19:00", &rice, 1.0),
+            ("2024-01-02", "12:30", &bun, 1.0),
+        ] {
+            let mut entry = Entry::new(food.clone(), quantity);
+            entry.time =
+                Some(NaiveTime::parse_from_str(time, TIME_FMT).unwrap());
+            log.add(date(day), entry);
+        }
+        log
+    }
+
+    /// the label and values of each row of the custom report grouped by
+    /// `group`
+    fn rows(group: &str) -> Vec<Vec<String>> {
+        let spec: Spec =
+            format!("R; metrics = calories, protein; group = {group}")
+                .parse()
+                .unwrap();
+        let lines = custom(&log(), &spec, date("2024-01-02"));
+        assert_eq!(lines[0], "2 logged days from 2023-12-04 to 2024-01-02");
+        lines[3..]
+            .iter()
+            .map(|line| {
+                let (label, values) = line.split_at(line.len() - 20);
+                let mut row = vec![label.trim().to_owned()];
+                row.extend(values.split_whitespace().map(str::to_owned));
+                row
+            })
+            .collect()
+    }
+
+    #[test]
+    fn custom_groups() {
+        assert_eq!(
+            rows("day"),
+            [
+                ["Mon 2024-01-01", "480", "12"],
+                ["Tue 2024-01-02", "140", "4"],
+            ]
+        );
+        // weeks and weekdays are averaged over the days logged in them
+        assert_eq!(rows("week"), [["week of 2024-01-01", "310", "8"]]);
+        assert_eq!(rows("month"), [["Jan 2024", "310", "8"]]);
+        assert_eq!(
+            rows("weekday"),
+            [["Mon", "480", "12"], ["Tue", "140", "4"]]
+        );
+        // meals are averaged over every logged day
+        assert_eq!(
+            rows("meal"),
+            [
+                ["Morning", "140", "4"],
+                ["Afternoon", "70", "2"],
+                ["Evening", "100", "2"],
+            ]
+        );
+        // foods are totaled, largest first
+        assert_eq!(rows("food"), [["Bun", "420", "12"], ["Rice", "200", "4"]]);
+    }
+}
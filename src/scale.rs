@@ -0,0 +1,44 @@
+//! weights read from a kitchen scale that reports them as lines of text over a
+//! serial port, like `/dev/ttyUSB0`
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// how long to wait for the scale to report a weight
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// the first number in `line`, ignoring the labels and units scales put around
+/// it, as in `ST,GS,+  123.4 g`
+fn parse(line: &str) -> Option<f64> {
+    line.split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .find_map(|s| s.parse().ok())
+}
+
+/// read the next weight reported by the scale at `path`
+pub fn read(path: &str) -> io::Result<f64> {
+    let path = path.to_owned();
+    let (tx, rx) = mpsc::channel();
+    // reading the device blocks until the scale sends a line, so read on
+    // another thread to be able to give up on a scale that is switched off
+    std::thread::spawn(move || {
+        let line = File::open(path).and_then(|f| {
+            let mut line = String::new();
+            BufReader::new(f).read_line(&mut line)?;
+            Ok(line)
+        });
+        let _ = tx.send(line);
+    });
+    let line = rx.recv_timeout(TIMEOUT).map_err(|_| {
+        io::Error::new(io::ErrorKind::TimedOut, "no reading from the scale")
+    })??;
+    parse(&line).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("no weight in `{}`", line.trim()),
+        )
+    })
+}
@@ -0,0 +1,511 @@
+//! matching of typed text against food names, fuzzily, by substring, or by
+//! regex
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+
+use crate::{log::Usage, Food};
+
+/// how typed text is matched against names
+#[derive(Clone, Copy, Default)]
+pub enum Mode {
+    /// the typed characters appear in order, not necessarily together
+    #[default]
+    Fuzzy,
+    /// the typed text appears as is, ignoring case
+    Substring,
+    /// the typed text is a regex matching part of the name, ignoring case
+    Regex,
+}
+
+impl Mode {
+    /// the mode after `self` when cycling through them
+    pub fn next(self) -> Self {
+        match self {
+            Mode::Fuzzy => Mode::Substring,
+            Mode::Substring => Mode::Regex,
+            Mode::Regex => Mode::Fuzzy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Fuzzy => "fuzzy",
+            Mode::Substring => "substring",
+            Mode::Regex => "regex",
+        }
+    }
+}
+
+/// typed text prepared for matching in one of the [Mode]s
+pub enum Query {
+    Fuzzy {
+        /// the lowercase characters of the text, without spaces
+        chars: Vec<char>,
+        /// the lowercase words of the text, for matching despite typos
+        words: Vec<Vec<char>>,
+    },
+    /// the lowercase text
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Query {
+    /// prepare `text` for matching in `mode`, or None if it is not a valid
+    /// regex, as while one is still being typed
+    pub fn new(mode: Mode, text: &str) -> Option<Self> {
+        Some(match mode {
+            Mode::Fuzzy => {
+                let text = text.to_lowercase();
+                Query::Fuzzy {
+                    chars: text
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .collect(),
+                    words: text
+                        .split_whitespace()
+                        .map(|w| w.chars().collect())
+                        .collect(),
+                }
+            }
+            Mode::Substring => Query::Substring(text.to_lowercase()),
+            Mode::Regex => Query::Regex(
+                RegexBuilder::new(text)
+                    .case_insensitive(true)
+                    .build()
+                    .ok()?,
+            ),
+        })
+    }
+
+    /// score how well the query matches `name`, or None if it does not. exact
+    /// matches only prefer shorter names
+    pub fn score(&self, name: &str) -> Option<i64> {
+        self.score_text(&Text::new(name))
+    }
+
+    /// the indices of the characters in `name` that the query matched, for
+    /// showing why it matched. these are empty if it did not match the name
+    pub fn positions(&self, name: &str) -> Vec<usize> {
+        let text = Text::new(name);
+        // the character indices of the byte range `start..end` of the name
+        let span = |start: usize, end: usize| {
+            text.lower[..start].chars().count()
+                ..text.lower[..end].chars().count()
+        };
+        match self {
+            Query::Fuzzy { chars, words } => positions(chars, &text.chars)
+                .or_else(|| typo_positions(words, &text.chars))
+                .unwrap_or_default(),
+            Query::Substring(query) if !query.is_empty() => text
+                .lower
+                .find(query.as_str())
+                .map(|i| span(i, i + query.len()).collect())
+                .unwrap_or_default(),
+            Query::Substring(_) => Vec::new(),
+            Query::Regex(re) => re
+                .find(&text.lower)
+                .map(|m| span(m.start(), m.end()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn score_text(&self, text: &Text) -> Option<i64> {
+        let matched = match self {
+            Query::Fuzzy { chars, words } => {
+                return score(chars, &text.chars)
+                    .or_else(|| typo_score(words, &text.chars));
+            }
+            Query::Substring(query) => text.lower.contains(query),
+            Query::Regex(re) => re.is_match(&text.lower),
+        };
+        matched.then(|| -(text.chars.len() as i64))
+    }
+}
+
+/// a name or keyword prepared for matching
+struct Text {
+    lower: String,
+    chars: Vec<char>,
+}
+
+impl Text {
+    fn new(s: &str) -> Self {
+        let lower = s.to_lowercase();
+        let chars = lower.chars().collect();
+        Self { lower, chars }
+    }
+}
+
+/// the bit in a [Haystack] mask for `c`: one for each letter and digit, and
+/// one shared by everything else
+fn bit(c: char) -> u64 {
+    match c {
+        'a'..='z' => 1 << (c as u32 - 'a' as u32),
+        '0'..='9' => 1 << (26 + c as u32 - '0' as u32),
+        _ => 1 << 36,
+    }
+}
+
+/// the texts of one food, with a mask of the characters appearing in any of
+/// them
+struct Haystack {
+    /// the name, then the keywords
+    texts: Vec<Text>,
+    mask: u64,
+}
+
+/// the foods prepared once for searching, so that each search can skip most
+/// of them without looking at their names. fuzzy queries skip the foods
+/// missing any of their characters, and substring queries the foods missing
+/// any of their three-character sequences
+pub struct Index {
+    haystacks: Vec<Haystack>,
+    /// the foods containing each sequence of three characters
+    trigrams: HashMap<[char; 3], Vec<usize>>,
+}
+
+impl Index {
+    pub fn new(foods: &[Food]) -> Self {
+        let mut trigrams: HashMap<[char; 3], Vec<usize>> = HashMap::new();
+        let mut haystacks = Vec::with_capacity(foods.len());
+        for (i, food) in foods.iter().enumerate() {
+            let texts: Vec<Text> = [&food.name]
+                .into_iter()
+                .chain(&food.keywords)
+                .map(|s| Text::new(s))
+                .collect();
+            let mut mask = 0;
+            let mut seen = HashSet::new();
+            for text in &texts {
+                mask |= text.chars.iter().fold(0, |m, &c| m | bit(c));
+                for w in text.chars.windows(3) {
+                    if seen.insert([w[0], w[1], w[2]]) {
+                        trigrams.entry([w[0], w[1], w[2]]).or_default().push(i);
+                    }
+                }
+            }
+            haystacks.push(Haystack { texts, mask });
+        }
+        Self {
+            haystacks,
+            trigrams,
+        }
+    }
+
+    /// the indices of the foods that `query` could match
+    fn candidates(&self, query: &Query) -> Vec<usize> {
+        let all = || (0..self.haystacks.len()).collect();
+        match query {
+            Query::Fuzzy { chars, words } => {
+                // every typo can bring in at most one missing character
+                let typos: usize = words.iter().map(|w| typos(w)).sum();
+                let mask = chars.iter().fold(0, |m, &c| m | bit(c));
+                (0..self.haystacks.len())
+                    .filter(|&i| {
+                        let missing = mask & !self.haystacks[i].mask;
+                        missing.count_ones() as usize <= typos
+                    })
+                    .collect()
+            }
+            Query::Substring(query) => {
+                let chars: Vec<char> = query.chars().collect();
+                let mut lists: Vec<&[usize]> = Vec::new();
+                for w in chars.windows(3) {
+                    match self.trigrams.get(&[w[0], w[1], w[2]]) {
+                        Some(list) => lists.push(list),
+                        None => return Vec::new(),
+                    }
+                }
+                // intersect the sorted lists, starting from the shortest
+                lists.sort_by_key(|list| list.len());
+                let Some((first, rest)) = lists.split_first() else {
+                    return all();
+                };
+                first
+                    .iter()
+                    .copied()
+                    .filter(|i| rest.iter().all(|l| l.binary_search(i).is_ok()))
+                    .collect()
+            }
+            Query::Regex(_) => all(),
+        }
+    }
+}
+
+/// the number of days after which the bonus for using a food is halved
+const HALF_LIFE: f64 = 30.0;
+
+/// score how well `query` matches `name`, or None if the characters of `query`
+/// do not all appear in `name` in order. matching ignores case and any spaces
+/// in `query`. characters matched at the start of a word or right after the
+/// previous match count extra, and longer names count a little less
+fn score(query: &[char], name: &[char]) -> Option<i64> {
+    let mut score = 0;
+    let mut start = 0;
+    let mut prev = None;
+    for &q in query {
+        let i = start + name[start..].iter().position(|&c| c == q)?;
+        score += 10;
+        if i == 0 || !name[i - 1].is_alphanumeric() {
+            score += 80;
+        }
+        if prev == Some(i.wrapping_sub(1)) {
+            score += 50;
+        }
+        prev = Some(i);
+        start = i + 1;
+    }
+    Some(score - name.len() as i64)
+}
+
+/// the indices of the characters in `name` matched by [score]
+fn positions(query: &[char], name: &[char]) -> Option<Vec<usize>> {
+    let mut ret = Vec::with_capacity(query.len());
+    let mut start = 0;
+    for &q in query {
+        let i = start + name[start..].iter().position(|&c| c == q)?;
+        ret.push(i);
+        start = i + 1;
+    }
+    Some(ret)
+}
+
+/// the number of typos allowed in a query word: none in short words, where
+/// they would match nearly anything, and one per four characters otherwise
+fn typos(word: &[char]) -> usize {
+    word.len() / 4
+}
+
+/// the edit distance between `word` and the closest prefix of `name`, if it is
+/// at most `max`, counting swapped neighbors as one edit. comparing against
+/// prefixes lets a partly typed word match
+fn prefix_distance(word: &[char], name: &[char], max: usize) -> Option<usize> {
+    // the distances from the first i characters of `word` to each prefix of
+    // `name`, one row of i at a time, along with the row before
+    let mut prev: Vec<usize> = Vec::new();
+    let mut row: Vec<usize> = (0..=name.len()).collect();
+    for (i, &w) in word.iter().enumerate() {
+        let mut next = vec![i + 1; name.len() + 1];
+        for (j, &n) in name.iter().enumerate() {
+            let sub = row[j] + usize::from(w != n);
+            next[j + 1] = sub.min(row[j + 1] + 1).min(next[j] + 1);
+            if i > 0 && j > 0 && w == name[j - 1] && word[i - 1] == n {
+                next[j + 1] = next[j + 1].min(prev[j - 1] + 1);
+            }
+        }
+        if next.iter().all(|&d| d > max) {
+            return None;
+        }
+        prev = std::mem::replace(&mut row, next);
+    }
+    row.into_iter().min().filter(|&d| d <= max)
+}
+
+/// score a match of the query `words` against `name` allowing for [typos],
+/// where each query word has to be close to the start of a word in the name.
+/// this is only a fallback for when [score] finds no match, so the scores are
+/// lower than those of exact matches
+fn typo_score(words: &[Vec<char>], name: &[char]) -> Option<i64> {
+    if words.is_empty() {
+        return None;
+    }
+    let name_words: Vec<&[char]> = name
+        .split(|c| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut score = 0;
+    for word in words {
+        let max = typos(word);
+        let distance = name_words
+            .iter()
+            .filter_map(|n| prefix_distance(word, n, max))
+            .min()?;
+        score += 10 * word.len() as i64 - 60 * distance as i64;
+    }
+    Some(score - name.len() as i64)
+}
+
+/// the indices of the characters in `name` matched by [typo_score]: the start
+/// of the closest word in the name for each query word, as long as the query
+/// word
+fn typo_positions(words: &[Vec<char>], name: &[char]) -> Option<Vec<usize>> {
+    // the start and length of each word in the name
+    let mut name_words = Vec::new();
+    let mut start = 0;
+    for (i, c) in name.iter().chain([&' ']).enumerate() {
+        if !c.is_alphanumeric() {
+            if i > start {
+                name_words.push((start, i - start));
+            }
+            start = i + 1;
+        }
+    }
+    let mut ret = Vec::new();
+    for word in words {
+        let max = typos(word);
+        let (start, len) = name_words
+            .iter()
+            .filter_map(|&(start, len)| {
+                let n = &name[start..start + len];
+                Some((prefix_distance(word, n, max)?, start, len))
+            })
+            .min()
+            .map(|(_, start, len)| (start, len))?;
+        ret.extend(start..start + len.min(word.len()));
+    }
+    Some(ret)
+}
+
+/// extra score for a food with `usage` as of `today`, which grows with the log
+/// of the number of times it was logged and halves every [HALF_LIFE] days since
+/// it was last logged. this is enough to order similar matches but not to lift
+/// a food above one matching much better
+fn usage_bonus(usage: &Usage, today: NaiveDate) -> i64 {
+    let days = (today - usage.last).num_days().max(0) as f64;
+    let decay = 0.5_f64.powf(days / HALF_LIFE);
+    (30.0 * (usage.count as f64).ln_1p() * decay) as i64
+}
+
+/// score how well `query` matches a food by its name or, failing that, its
+/// keywords
+fn food_score(query: &Query, haystack: &Haystack) -> Option<i64> {
+    let (name, keywords) = haystack.texts.split_first()?;
+    query.score_text(name).or_else(|| {
+        keywords
+            .iter()
+            .filter_map(|word| query.score_text(word))
+            .max()
+    })
+}
+
+/// the at most `n` foods best matching `query`, best first, favoring those
+/// logged often and recently according to `usage`. `index` must have been
+/// built from `foods`
+pub fn best<'a>(
+    index: &Index,
+    query: &Query,
+    foods: &'a [Food],
+    usage: &HashMap<String, Usage>,
+    today: NaiveDate,
+    n: usize,
+) -> Vec<&'a Food> {
+    let mut scored: Vec<(i64, &Food)> = index
+        .candidates(query)
+        .into_iter()
+        .filter_map(|i| {
+            let food = &foods[i];
+            let bonus = usage
+                .get(food.name.as_str())
+                .map_or(0, |usage| usage_bonus(usage, today));
+            Some((food_score(query, &index.haystacks[i])? + bonus, food))
+        })
+        .collect();
+    // a stable sort keeps ties in database order
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().take(n).map(|(_, food)| food).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 6] = [
+        "Chicken Breast",
+        "Chickpeas",
+        "Beef Brisket",
+        "Cheddar Cheese",
+        "Oatmeal",
+        "Peach",
+    ];
+
+    fn foods() -> Vec<Food> {
+        NAMES
+            .iter()
+            .map(|name| format!("{name}\t100\t1\t1\t1\tg").parse().unwrap())
+            .collect()
+    }
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    /// the names of the foods best matching `text` in `mode`
+    fn search(mode: Mode, text: &str) -> Vec<String> {
+        let foods = foods();
+        let index = Index::new(&foods);
+        let query = Query::new(mode, text).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        best(&index, &query, &foods, &HashMap::new(), today, foods.len())
+            .into_iter()
+            .map(|food| food.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn misspelled_words() {
+        assert_eq!(search(Mode::Fuzzy, "chiken brest")[0], "Chicken Breast");
+        assert_eq!(search(Mode::Fuzzy, "chikcen")[0], "Chicken Breast");
+        assert_eq!(search(Mode::Fuzzy, "oatmel"), ["Oatmeal"]);
+    }
+
+    #[test]
+    fn transposition() {
+        let distance =
+            |word, name| prefix_distance(&chars(word), &chars(name), 2);
+        assert_eq!(distance("chikcen", "chicken"), Some(1));
+        assert_eq!(distance("brest", "breast"), Some(1));
+        assert_eq!(distance("chick", "chicken"), Some(0));
+        assert_eq!(distance("xyzzy", "chicken"), None);
+    }
+
+    #[test]
+    fn typo_score_needs_every_word() {
+        let words = [chars("chikcen"), chars("brest")];
+        assert!(typo_score(&words, &chars("chicken breast")).is_some());
+        assert!(typo_score(&words, &chars("chicken thigh")).is_none());
+        // short words allow no typos
+        assert!(typo_score(&[chars("pch")], &chars("peach")).is_none());
+    }
+
+    #[test]
+    fn query_shorter_than_trigram() {
+        let mut found = search(Mode::Substring, "ch");
+        found.sort();
+        let names = ["Cheddar Cheese", "Chicken Breast", "Chickpeas", "Peach"];
+        assert_eq!(found, names);
+        assert_eq!(search(Mode::Substring, ""), search(Mode::Regex, ""));
+    }
+
+    #[test]
+    fn index_finds_every_match() {
+        let foods = foods();
+        let index = Index::new(&foods);
+        let queries = [
+            (Mode::Fuzzy, "ch"),
+            (Mode::Fuzzy, "chiken brest"),
+            (Mode::Fuzzy, "bf brskt"),
+            (Mode::Fuzzy, "zz"),
+            (Mode::Substring, "che"),
+            (Mode::Substring, "each"),
+            (Mode::Substring, "ea"),
+            (Mode::Substring, "cheddar cheese"),
+            (Mode::Regex, "^c.*e$"),
+        ];
+        for (mode, text) in queries {
+            let query = Query::new(mode, text).unwrap();
+            let candidates = index.candidates(&query);
+            for (i, haystack) in index.haystacks.iter().enumerate() {
+                if food_score(&query, haystack).is_some() {
+                    assert!(
+                        candidates.contains(&i),
+                        "`{text}` matches {} but it was skipped",
+                        NAMES[i]
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! words that searches for a food match besides its name: its brand, tags, and
+//! aliases. these are kept apart from the foods file, which is shared with
+//! other programs, in a tsv file of food names, brands, and comma-separated
+//! tags and aliases, like a line of `Kirkland Peanut Butter`, `Costco`,
+//! `snack, spread`, and `pb`
+
+use std::{io, path::Path};
+
+use crate::Food;
+
+/// load the words from `path` into the matching `foods`. a missing file is
+/// treated as empty, and lines for unknown foods are ignored
+pub fn load(path: impl AsRef<Path>, foods: &mut [Food]) -> io::Result<()> {
+    let s = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for line in s.lines().filter(|line| !line.starts_with('#')) {
+        let mut fields = line.split('\t');
+        let name = fields.next().unwrap_or_default();
+        let Some(food) = foods.iter_mut().find(|f| f.name == name) else {
+            continue;
+        };
+        let words = fields
+            .flat_map(|field| field.split(','))
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(str::to_owned);
+        food.keywords.extend(words);
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! daily calorie and macro goals, with one-off overrides for particular days
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::NaiveDate;
+
+use crate::log::DATE_FMT;
+
+/// targets for a day's calories and macros. any of them may be unset
+#[derive(Clone, Copy, Default)]
+pub struct Goals {
+    pub calories: Option<f64>,
+    pub protein: Option<f64>,
+    pub carbs: Option<f64>,
+    pub fat: Option<f64>,
+}
+
+impl Goals {
+    /// the goals in the order calories, protein, carbs, fat
+    pub fn fields(&self) -> [Option<f64>; 4] {
+        [self.calories, self.protein, self.carbs, self.fat]
+    }
+}
+
+/// the default goals from the config and any overrides for single days, stored
+/// as a tsv file of dates and goals
+pub struct Targets {
+    path: PathBuf,
+    defaults: Goals,
+    days: BTreeMap<NaiveDate, Goals>,
+}
+
+impl Display for Goals {
+    /// the goals as calories, protein, carbs, and fat separated by spaces, with
+    /// `-` for goals that are not set
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self
+            .fields()
+            .iter()
+            .map(|v| v.map_or("-".to_owned(), |v| v.to_string()))
+            .collect();
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+impl FromStr for Goals {
+    type Err = String;
+
+    /// parse the format written by [Display], allowing trailing goals to be
+    /// left off
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = [None; 4];
+        let values: Vec<&str> = s.split_whitespace().collect();
+        if values.len() > fields.len() {
+            return Err("too many goals".to_owned());
+        }
+        for (field, value) in fields.iter_mut().zip(values) {
+            if value != "-" {
+                let v = value.parse().map_err(|_| format!("bad `{value}`"))?;
+                *field = Some(v);
+            }
+        }
+        let [calories, protein, carbs, fat] = fields;
+        Ok(Self {
+            calories,
+            protein,
+            carbs,
+            fat,
+        })
+    }
+}
+
+impl Targets {
+    /// load the overrides from `path`, falling back on `defaults` for other
+    /// days. a missing file is treated as empty
+    pub fn load(path: impl AsRef<Path>, defaults: Goals) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let s = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let days = s
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let (date, goals) = line.split_once('\t')?;
+                let date = NaiveDate::parse_from_str(date, DATE_FMT).ok()?;
+                Some((date, goals.replace('\t', " ").parse().ok()?))
+            })
+            .collect();
+        Ok(Self {
+            path,
+            defaults,
+            days,
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut s = String::from("# Date\tCalories\tProtein\tCarbs\tFat\n");
+        for (date, goals) in &self.days {
+            let goals = goals.to_string().replace(' ', "\t");
+            s.push_str(&format!("{}\t{goals}\n", date.format(DATE_FMT)));
+        }
+        std::fs::write(&self.path, s)
+    }
+
+    /// the goals from the config
+    pub fn defaults(&self) -> &Goals {
+        &self.defaults
+    }
+
+    /// the goals in effect on `date`
+    pub fn get(&self, date: NaiveDate) -> &Goals {
+        self.days.get(&date).unwrap_or(&self.defaults)
+    }
+
+    /// the goals set for `date` alone, if any
+    pub fn get_override(&self, date: NaiveDate) -> Option<&Goals> {
+        self.days.get(&date)
+    }
+
+    /// set the goals for `date` alone or, with None, go back to the defaults
+    pub fn set_override(&mut self, date: NaiveDate, goals: Option<Goals>) {
+        match goals {
+            Some(goals) => self.days.insert(date, goals),
+            None => self.days.remove(&date),
+        };
+    }
+}
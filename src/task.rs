@@ -0,0 +1,60 @@
+//! work too slow to do between keypresses, like reading a scale, run on another
+//! thread so that the event loop can keep the screen alive with a spinner and
+//! give up on it when asked
+
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// how long each frame of the spinner is shown
+pub const FRAME: Duration = Duration::from_millis(100);
+
+/// the frames of the spinner, in order
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// a result being worked out on another thread. dropping the task gives up on
+/// it, discarding the result whenever it arrives
+pub struct Task<T> {
+    /// what the task is doing, as shown next to the spinner
+    pub label: &'static str,
+    rx: Receiver<io::Result<T>>,
+    started: Instant,
+}
+
+impl<T: Send + 'static> Task<T> {
+    /// start running `f` on another thread
+    pub fn spawn(
+        label: &'static str,
+        f: impl FnOnce() -> io::Result<T> + Send + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(f());
+        });
+        Self {
+            label,
+            rx,
+            started: Instant::now(),
+        }
+    }
+
+    /// the result of the task, once it has finished
+    pub fn poll(&self) -> Option<io::Result<T>> {
+        match self.rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(io::Error::other(format!("{} failed", self.label))))
+            }
+        }
+    }
+
+    /// the frame of the spinner to show now
+    pub fn spinner(&self) -> char {
+        let frame = self.started.elapsed().as_millis() / FRAME.as_millis();
+        SPINNER[frame as usize % SPINNER.len()]
+    }
+}
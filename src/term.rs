@@ -0,0 +1,71 @@
+//! putting the terminal into raw mode for the TUI and taking it back out,
+//! whether the program finishes normally, returns an error, or panics
+
+use std::{
+    io::{self, stdout},
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    cursor,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture,
+    },
+    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    ExecutableCommand,
+};
+
+/// whether the terminal is in raw mode and still needs restoring
+static RAW: AtomicBool = AtomicBool::new(false);
+
+/// the terminal in raw mode, with bracketed paste and mouse capture, until
+/// this is dropped
+pub struct RawMode;
+
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        enable_raw_mode()?;
+        RAW.store(true, Ordering::SeqCst);
+        stdout()
+            .execute(EnableBracketedPaste)?
+            .execute(EnableMouseCapture)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// leave raw mode and restore the terminal to how it was before starting, if
+/// it hasn't been already. errors are ignored since this runs while already
+/// giving up
+fn restore() {
+    if !RAW.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let _ = disable_raw_mode();
+    let _ = (|| -> io::Result<()> {
+        stdout()
+            .execute(DisableBracketedPaste)?
+            .execute(DisableMouseCapture)?
+            .execute(Clear(ClearType::All))?
+            .execute(cursor::RestorePosition)?
+            .execute(cursor::Show)?;
+        Ok(())
+    })();
+}
+
+/// restore the terminal before a panic message is printed, so that it isn't
+/// mangled by raw mode or cleared away afterwards
+pub fn restore_on_panic() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        hook(info);
+    }));
+}
@@ -0,0 +1,110 @@
+//! the colors of the interface, each set in the config with a key like
+//!
+//! ```text
+//! color_accent = dark_cyan
+//! color_over = #ff5f5f
+//! ```
+//!
+//! where the color is one of crossterm's names or a hex code
+
+use crossterm::style::Color;
+
+/// the color for each part of the interface that is colored
+#[derive(Clone, Copy)]
+pub struct Theme {
+    /// titles and the keys in the help bar
+    pub accent: Color,
+    /// totals that have gone over their goals
+    pub over: Color,
+    /// totals getting close to their goals
+    pub near: Color,
+    /// days close to their budget on the Calendar and status messages
+    pub good: Color,
+    /// errors and invalid fields
+    pub error: Color,
+    /// notes on foods
+    pub note: Color,
+    /// the characters of food names matched by a search
+    pub matched: Color,
+    /// values that differ noticeably between the days or foods compared
+    pub changed: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Blue,
+            over: Color::Red,
+            near: Color::Yellow,
+            good: Color::Green,
+            error: Color::Red,
+            note: Color::Yellow,
+            matched: Color::Cyan,
+            changed: Color::Yellow,
+        }
+    }
+}
+
+/// parse a color name like `dark_cyan` or a hex code like `#00afaf`
+fn parse(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let n = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)?;
+        let [_, r, g, b] = n.to_be_bytes();
+        return Some(Color::Rgb { r, g, b });
+    }
+    Color::try_from(s).ok()
+}
+
+impl Theme {
+    /// set the color for `role` to `value`. if either is unknown, the color is
+    /// left unchanged and the error says which
+    pub fn set(&mut self, role: &str, value: &str) -> Result<(), String> {
+        let color =
+            parse(value).ok_or_else(|| format!("unknown color `{value}`"))?;
+        let field = match role {
+            "accent" => &mut self.accent,
+            "over" => &mut self.over,
+            "near" => &mut self.near,
+            "good" => &mut self.good,
+            "error" => &mut self.error,
+            "note" => &mut self.note,
+            "matched" => &mut self.matched,
+            "changed" => &mut self.changed,
+            _ => return Err(format!("unknown color role `{role}`")),
+        };
+        *field = color;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_reports_unknown_colors_and_roles() {
+        let mut theme = Theme::default();
+        assert!(theme.set("over", "#ff5f5f").is_ok());
+        assert!(matches!(
+            theme.over,
+            Color::Rgb {
+                r: 255,
+                g: 95,
+                b: 95
+            }
+        ));
+        assert!(theme.set("over", "#ff5f5").is_err());
+        assert!(theme.set("over", "reddish").is_err());
+        assert!(theme.set("bogus", "red").is_err());
+        assert!(matches!(
+            theme.over,
+            Color::Rgb {
+                r: 255,
+                g: 95,
+                b: 95
+            }
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+//! conversions between units of the same kind, like ounces and grams or cups
+//! and tablespoons
+
+/// what a unit measures
+#[derive(PartialEq)]
+enum Kind {
+    Mass,
+    Volume,
+}
+
+/// the kind of `unit` and its size in grams or milliliters, if it is a known
+/// unit. case, a trailing period, and a plural `s` are ignored
+fn size(unit: &str) -> Option<(Kind, f64)> {
+    let unit = unit.trim().trim_end_matches('.').to_lowercase();
+    let unit = match unit.strip_suffix('s') {
+        Some(u) if !u.is_empty() && u != "l" => u,
+        _ => &unit,
+    };
+    Some(match unit {
+        "mg" | "milligram" => (Kind::Mass, 0.001),
+        "g" | "gram" => (Kind::Mass, 1.0),
+        "kg" | "kilogram" => (Kind::Mass, 1000.0),
+        "oz" | "ounce" => (Kind::Mass, 28.3495),
+        "lb" | "pound" => (Kind::Mass, 453.592),
+        "ml" | "milliliter" | "millilitre" => (Kind::Volume, 1.0),
+        "l" | "liter" | "litre" => (Kind::Volume, 1000.0),
+        "tsp" | "teaspoon" => (Kind::Volume, 4.92892),
+        "tbsp" | "tablespoon" => (Kind::Volume, 14.7868),
+        "fl oz" | "floz" | "fluid ounce" => (Kind::Volume, 29.5735),
+        "cup" => (Kind::Volume, 236.588),
+        _ => return None,
+    })
+}
+
+/// whether `unit` is one that [convert] knows
+pub fn is_known(unit: &str) -> bool {
+    size(unit).is_some()
+}
+
+/// convert `quantity` of `from` into `to`, if both are known units of the same
+/// kind
+pub fn convert(quantity: f64, from: &str, to: &str) -> Option<f64> {
+    let (from_kind, from) = size(from)?;
+    let (to_kind, to) = size(to)?;
+    (from_kind == to_kind).then(|| quantity * from / to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-3)
+    }
+
+    #[test]
+    fn conversions() {
+        assert!(close(convert(2.0, "oz", "g"), 56.699));
+        assert!(close(convert(1.0, "kg", "lb"), 2.20462));
+        assert!(close(convert(1.0, "Cups", "tbsp."), 16.0));
+        assert!(close(convert(500.0, "ml", "L"), 0.5));
+        assert_eq!(convert(1.0, "cup", "g"), None);
+        assert_eq!(convert(1.0, "slice", "g"), None);
+    }
+
+    #[test]
+    fn known_units() {
+        for unit in ["g", "Grams", "lbs.", "l", "fl oz", "Tablespoons"] {
+            assert!(is_known(unit), "{unit}");
+        }
+        for unit in ["s", "slice", "egg", ""] {
+            assert!(!is_known(unit), "{unit}");
+        }
+    }
+}